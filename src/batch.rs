@@ -0,0 +1,154 @@
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::result;
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::Value;
+use crate::cli::{Command, OutputFormat};
+use crate::handlers::{CommandHandler, CommandHandlerError};
+use crate::input::InputSource;
+use crate::stream;
+
+#[derive(Serialize)]
+struct LineResult<'a> {
+    line: usize,
+    input: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<LineError>
+}
+
+#[derive(Serialize)]
+struct LineError {
+    code: i32,
+    message: String
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Highest exit code of the failed lines
+    pub exit_code: i32
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Processed {} lines: {} succeeded, {} failed", self.succeeded + self.failed, self.succeeded, self.failed)
+    }
+}
+
+/// Runs the command for every line of the batch file (or stdin) and returns the exit code of the whole batch
+pub fn run(command: Command, batch: Option<&str>, format: &OutputFormat, output: Option<&str>) -> result::Result<i32, CommandHandlerError> {
+    if let Command::Shell = command {
+        return Err(CommandHandlerError::InvalidArguments(String::from("The interactive shell can't be used in batch mode!")));
+    }
+    if stream::requested(&command) {
+        return Err(CommandHandlerError::InvalidArguments(String::from("--stream can't be used in batch mode!")));
+    }
+
+    let reader: Box<dyn BufRead> = match batch {
+        Some(path) => Box::new(BufReader::new(File::open(path)
+            .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to open the batch file '{path}'! {err}"))))?)),
+        None => Box::new(io::stdin().lock())
+    };
+    let mut writer: Box<dyn Write> = match output {
+        Some(path) => Box::new(File::create(path)
+            .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to create the output file '{path}'! {err}"))))?),
+        None => Box::new(io::stdout().lock())
+    };
+
+    let summary = process(command, reader, &mut writer, format)?;
+    let message = summary.to_string();
+    eprintln!("{}", if summary.failed == 0 {message.green()} else {message.yellow()});
+    Ok(summary.exit_code)
+}
+
+/// Writes one result per non-empty input line (one JSON object per line for `--output-format json`). Failed lines don't stop the batch.
+fn process(command: Command, reader: impl BufRead, writer: &mut impl Write, format: &OutputFormat) -> result::Result<Summary, CommandHandlerError> {
+    let write_error = |err: io::Error| CommandHandlerError::RuntimeError(Some(format!("Failed to write the result! {err}")));
+
+    let mut handler = CommandHandler::new(command);
+    let mut summary = Summary::default();
+    for (line_i, line) in reader.lines().enumerate() {
+        let line = line.map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to read line {}! {err}", line_i + 1))))?;
+        let input = line.strip_suffix('\r').unwrap_or(&line);
+        if input.trim().is_empty() {
+            continue;
+        }
+
+        handler = handler.with_stdin(InputSource::from_bytes(input.as_bytes()));
+        let result = handler.handle();
+        let mut line_result = LineResult {line: line_i + 1, input, output: None, error: None};
+        match result {
+            Ok(result) => {
+                summary.succeeded += 1;
+                match format {
+                    OutputFormat::Text => writeln!(writer, "{}", result.text).map_err(write_error)?,
+                    OutputFormat::Json => line_result.output = Some(result.data)
+                }
+            },
+            Err(err) => {
+                summary.failed += 1;
+                summary.exit_code = summary.exit_code.max(err.exit_code());
+                match format {
+                    // An empty line keeps the results aligned with the input
+                    OutputFormat::Text => {
+                        eprintln!("{} {err}", format!("line {}:", line_i + 1).red().bold());
+                        writeln!(writer).map_err(write_error)?
+                    },
+                    OutputFormat::Json => line_result.error = Some(LineError {code: err.exit_code(), message: err.to_string()})
+                }
+            }
+        }
+
+        if *format == OutputFormat::Json {
+            writeln!(writer, "{}", serde_json::to_string(&line_result).unwrap_or_default()).map_err(write_error)?;
+        }
+    }
+
+    writer.flush().map_err(write_error)?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use pretty_assertions::assert_eq;
+    use crate::cli::{DecodeMethod, HexOptions};
+    use crate::input::Input;
+    use crate::utils::TestResult;
+    use super::*;
+
+    fn decode_hex() -> Command {
+        Command::Decode {method: DecodeMethod::Hex {options: HexOptions {text: Input::Stdin}}}
+    }
+
+    #[test]
+    fn continue_after_failed_lines() -> TestResult {
+        let mut output = Vec::new();
+        let summary = process(decode_hex(), Cursor::new("6478\nnot hex\r\n\n6f6b\n"), &mut output, &OutputFormat::Text)?;
+
+        assert_eq!(String::from_utf8(output).map_err(|err| err.to_string())?, "dx\n\nok\n");
+        assert_eq!(summary, Summary {succeeded: 2, failed: 1, exit_code: 3});
+        Ok(())
+    }
+
+    #[test]
+    fn write_ndjson_results() -> TestResult {
+        let mut output = Vec::new();
+        process(decode_hex(), Cursor::new("6478\nxyz"), &mut output, &OutputFormat::Json)?;
+
+        let lines = String::from_utf8(output).map_err(|err| err.to_string())?
+            .lines()
+            .map(serde_json::from_str::<Value>)
+            .collect::<result::Result<Vec<Value>, serde_json::Error>>()
+            .map_err(|err| err.to_string())?;
+        assert_eq!(lines[0], serde_json::json!({"line": 1, "input": "6478", "output": "dx"}));
+        assert_eq!(lines[1]["input"], "xyz");
+        assert_eq!(lines[1]["error"]["code"], 3);
+        Ok(())
+    }
+}
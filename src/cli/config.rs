@@ -0,0 +1,35 @@
+use clap::{Args, Subcommand};
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigMethod {
+    #[command(about = "Show the effective configuration and the files it was loaded from")]
+    Show,
+    #[command(about = "Get a configuration value")]
+    Get {
+        #[command(flatten)]
+        options: ConfigGetOptions
+    },
+    #[command(about = "Set a configuration value")]
+    Set {
+        #[command(flatten)]
+        options: ConfigSetOptions
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct ConfigGetOptions {
+    #[arg(required = true, help = "Key in the form 'section.name', e.g. 'time.timezone'")]
+    pub key: String
+}
+
+#[derive(Debug, Args)]
+pub struct ConfigSetOptions {
+    #[arg(required = true, help = "Key in the form 'section.name', e.g. 'time.timezone'")]
+    pub key: String,
+
+    #[arg(required = true, help = "New value")]
+    pub value: String,
+
+    #[arg(long, required = false, help = "Write to the project-local .dx.toml in the current directory instead of the user config")]
+    pub local: bool
+}
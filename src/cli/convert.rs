@@ -0,0 +1,39 @@
+use clap::{Args, ValueEnum};
+use dx::convert::DataFormat;
+use crate::input::Input;
+
+#[derive(Debug, Args)]
+pub struct ConvertOptions {
+    #[arg(long, short, value_name = "path to file", conflicts_with = "content", help = "Path to the input file ('-' reads stdin)")]
+    pub file: Option<String>,
+
+    #[arg(long, value_name = "content", conflicts_with = "file", help = "Input content ('-' reads stdin). Stdin is used when neither file nor content is provided")]
+    pub content: Option<Input>,
+
+    #[arg(long, required = false, help = "Format of the input. Detected from the file extension when omitted")]
+    pub from: Option<ConvertFormat>,
+
+    #[arg(long, short, required = true, help = "Format of the output")]
+    pub to: ConvertFormat
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ConvertFormat {
+    Json,
+    Yaml,
+    Toml,
+    Json5,
+    Ndjson
+}
+
+impl From<ConvertFormat> for DataFormat {
+    fn from(value: ConvertFormat) -> Self {
+        match value {
+            ConvertFormat::Json => DataFormat::Json,
+            ConvertFormat::Yaml => DataFormat::Yaml,
+            ConvertFormat::Toml => DataFormat::Toml,
+            ConvertFormat::Json5 => DataFormat::Json5,
+            ConvertFormat::Ndjson => DataFormat::Ndjson
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use clap::{Args, Subcommand};
+use crate::input::Input;
+
+#[derive(Debug, Subcommand)]
+pub enum CsvMethod {
+    #[command(about = "Convert CSV rows to an array of JSON objects")]
+    ToJson {
+        #[command(flatten)]
+        options: CsvToJsonOptions
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct CsvToJsonOptions {
+    #[arg(long, short, value_name = "path to file", conflicts_with = "content", help = "Path to CSV file ('-' reads stdin)")]
+    pub file: Option<String>,
+
+    #[arg(long, value_name = "csv content", conflicts_with = "file", help = "CSV content ('-' reads stdin). Stdin is used when neither file nor content is provided")]
+    pub content: Option<Input>,
+
+    #[arg(long, short, required = false, default_value = ",", help = "Field delimiter (e.g. ';' or $'\\t')")]
+    pub delimiter: char,
+
+    #[arg(long, required = false, help = "Keep every value as a string instead of inferring numbers, booleans and nulls")]
+    pub no_infer: bool
+}
@@ -1,4 +1,5 @@
-use clap::{Args, Subcommand};
+use clap::{ArgAction, Args, Subcommand};
+use crate::input::Input;
 
 #[derive(Debug, Subcommand)]
 pub enum EncodeMethod {
@@ -8,6 +9,7 @@ pub enum EncodeMethod {
         options: Base64Options
     },
     #[command(about = "URL encode")]
+    #[allow(clippy::upper_case_acronyms)]
     URL {
         #[command(flatten)]
         options: URLOptions
@@ -27,6 +29,7 @@ pub enum DecodeMethod {
         options: crate::cli::Base64Options
     },
     #[command(about = "URL decode")]
+    #[allow(clippy::upper_case_acronyms)]
     URL {
         #[command(flatten)]
         options: crate::cli::URLOptions
@@ -40,24 +43,33 @@ pub enum DecodeMethod {
 
 #[derive(Debug, Args)]
 pub struct Base64Options {
-    #[arg(required = true, help = "The text to be encoded")]
-    pub text: String,
+    #[arg(default_value = "-", hide_default_value = true, help = "The text to be encoded (reads stdin when omitted or '-')")]
+    pub text: Input,
 
-    #[arg(long, required = false, help = "If the generated string should be url safe")]
+    #[arg(long, required = false, action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value = "false", default_missing_value = "true", help = "If the generated string should be url safe")]
     pub url_safe: bool,
 
-    #[arg(long, required = false, help = "If the generated string should have padding bytes at the end")]
+    #[arg(long, required = false, action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value = "false", default_missing_value = "true", help = "If the generated string should have padding bytes at the end")]
     pub no_padding: bool
 }
 
+impl From<&Base64Options> for dx::encode::Base64Config {
+    fn from(value: &Base64Options) -> Self {
+        dx::encode::Base64Config {
+            url_safe: value.url_safe,
+            no_padding: value.no_padding
+        }
+    }
+}
+
 #[derive(Debug, Args)]
 pub struct URLOptions {
-    #[arg(required = true, help = "Text to be encoded")]
-    pub text: String
+    #[arg(default_value = "-", hide_default_value = true, help = "Text to be encoded (reads stdin when omitted or '-')")]
+    pub text: Input
 }
 
 #[derive(Debug, Args)]
 pub struct HexOptions {
-    #[arg(required = true, help = "Text to be encoded")]
-    pub text: String
+    #[arg(default_value = "-", hide_default_value = true, help = "Text to be encoded (reads stdin when omitted or '-')")]
+    pub text: Input
 }
\ No newline at end of file
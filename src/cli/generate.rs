@@ -0,0 +1,14 @@
+use clap::Args;
+use clap_complete::Shell;
+
+#[derive(Debug, Args)]
+pub struct CompletionsOptions {
+    #[arg(help = "Shell to generate the completion script for")]
+    pub shell: Shell
+}
+
+#[derive(Debug, Args)]
+pub struct ManOptions {
+    #[arg(long, short, required = false, help = "Directory where a man page for every command is written (prints the main page when omitted)")]
+    pub dir: Option<String>
+}
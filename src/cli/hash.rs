@@ -1,4 +1,6 @@
 use clap::{Args, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use crate::input::Input;
 
 #[derive(Debug, Subcommand)]
 pub enum HashMethod {
@@ -31,8 +33,8 @@ pub enum HashMethod {
 
 #[derive(Debug, Args)]
 pub struct HashTextOptions {
-    #[arg(required = true, help = "Text to hash")]
-    pub text: String
+    #[arg(default_value = "-", hide_default_value = true, help = "Text to hash (reads stdin when omitted or '-')")]
+    pub text: Input
 }
 
 #[derive(Debug, Args)]
@@ -46,8 +48,8 @@ pub struct HashFileOptions {
 
 #[derive(Debug, Args)]
 pub struct HashVerifyOptions {
-    #[arg(required = true, help = "Text to hash")]
-    pub text: String,
+    #[arg(required = true, help = "Text to hash ('-' reads stdin)")]
+    pub text: Input,
 
     #[arg(required = true, help = "Expected hash")]
     pub expected: String,
@@ -56,9 +58,20 @@ pub struct HashVerifyOptions {
     pub algorithm: HashAlgorithm
 }
 
-#[derive(Debug, Clone, ValueEnum)]
+#[derive(Debug, Clone, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum HashAlgorithm {
     Md5,
     Sha256,
     Sha512
+}
+
+impl From<&HashAlgorithm> for dx::hash::HashAlgorithm {
+    fn from(value: &HashAlgorithm) -> Self {
+        match value {
+            HashAlgorithm::Md5 => dx::hash::HashAlgorithm::Md5,
+            HashAlgorithm::Sha256 => dx::hash::HashAlgorithm::Sha256,
+            HashAlgorithm::Sha512 => dx::hash::HashAlgorithm::Sha512,
+        }
+    }
 }
\ No newline at end of file
@@ -1,4 +1,12 @@
-use clap::{Args, Subcommand};
+use clap::{Args, Subcommand, ValueEnum};
+use dx::csv::ArrayMode;
+use dx::json::{Indent, Style};
+use dx::json::flatten::{ArrayNotation, PathStyle};
+use dx::json::schema::SchemaDraft;
+use dx::json::stream::Layout;
+use dx::json::types::Language;
+use crate::cli::HashAlgorithm;
+use crate::input::Input;
 
 #[derive(Debug, Subcommand)]
 pub enum JSONMethod {
@@ -16,33 +24,494 @@ pub enum JSONMethod {
     Validate {
         #[command(flatten)]
         options: ValidateOptions
+    },
+    #[command(about = "Query JSON with a jq expression")]
+    Query {
+        #[command(flatten)]
+        options: QueryOptions
+    },
+    #[command(about = "Get values at a JSON Pointer or JSONPath")]
+    Get {
+        #[command(flatten)]
+        options: GetOptions
+    },
+    #[command(about = "Set the value at a JSON Pointer")]
+    Set {
+        #[command(flatten)]
+        options: SetOptions
+    },
+    #[command(about = "Delete the value at a JSON Pointer")]
+    Delete {
+        #[command(flatten)]
+        options: DeleteOptions
+    },
+    #[command(about = "Show the differences between two JSON documents")]
+    Diff {
+        #[command(flatten)]
+        options: DiffOptions
+    },
+    #[command(about = "Apply a JSON Patch or JSON Merge Patch")]
+    Patch {
+        #[command(flatten)]
+        options: PatchOptions
+    },
+    #[command(about = "Convert an array of objects to CSV")]
+    ToCsv {
+        #[command(flatten)]
+        options: ToCsvOptions
+    },
+    #[command(about = "Print the RFC 8785 canonical form of JSON")]
+    Canonicalize {
+        #[command(flatten)]
+        options: CanonicalizeOptions
+    },
+    #[command(about = "Hash the canonical form of JSON")]
+    Hash {
+        #[command(flatten)]
+        options: JSONHashOptions
+    },
+    #[command(about = "Generate type definitions from sample JSON documents")]
+    Types {
+        #[command(flatten)]
+        options: TypesOptions
+    },
+    #[command(about = "Flatten nested JSON into an object of key paths like 'a.b[0].c'")]
+    Flatten {
+        #[command(flatten)]
+        options: FlattenOptions
+    },
+    #[command(about = "Rebuild nested JSON from an object of key paths")]
+    Unflatten {
+        #[command(flatten)]
+        options: UnflattenOptions
+    },
+    #[command(about = "Work with JSON Schemas")]
+    Schema {
+        #[command(subcommand)]
+        method: SchemaMethod
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SchemaMethod {
+    #[command(about = "Infer a JSON Schema (draft 2020-12) from sample JSON documents")]
+    Infer {
+        #[command(flatten)]
+        options: SchemaInferOptions
     }
 }
 
 
 #[derive(Debug, Args)]
 pub struct FormatOptions {
-    #[arg(long, short, value_name = "path to file", conflicts_with = "content")]
+    #[arg(long, short, value_name = "path to file", conflicts_with = "content", help = "Path to JSON file ('-' reads stdin)")]
     pub file: Option<String>,
 
-    #[arg(long, value_name = "json content", conflicts_with = "file")]
-    pub content: Option<String>
+    #[arg(long, value_name = "json content", conflicts_with = "file", help = "JSON content ('-' reads stdin). Stdin is used when neither file nor content is provided")]
+    pub content: Option<Input>,
+
+    #[command(flatten)]
+    pub style: StyleOptions,
+
+    #[arg(long, short, required = false, requires = "file", help = "Write the formatted JSON back to the file instead of printing it")]
+    pub write: bool,
+
+    #[arg(long, required = false, conflicts_with = "stream", help = "Don't end the printed or written JSON with a line break")]
+    pub no_trailing_newline: bool,
+
+    #[arg(long, required = false, conflicts_with_all = ["content", "write"], help = "Process NDJSON value by value, or a top-level array item by item, without loading the whole input")]
+    pub stream: bool,
+
+    #[command(flatten)]
+    pub layout: StreamLayoutOptions
+}
+
+/// Layout of the printed JSON, shared by the commands which print whole documents
+#[derive(Debug, Args)]
+pub struct StyleOptions {
+    #[arg(long, required = false, default_value = "2", value_parser = clap::value_parser!(u8).range(0..=16), help = "Number of spaces per indentation level")]
+    pub indent: u8,
+
+    #[arg(long, required = false, conflicts_with = "indent", help = "Indent with tabs instead of spaces")]
+    pub tabs: bool,
+
+    #[arg(long, required = false, help = "Sort object keys alphabetically at every level (keys keep their original order otherwise)")]
+    pub sort_keys: bool,
+
+    #[arg(long, required = false, help = "Write arrays of numbers, strings, booleans and nulls on a single line")]
+    pub compact_arrays: bool
+}
+
+impl Default for StyleOptions {
+    fn default() -> Self {
+        StyleOptions {indent: 2, tabs: false, sort_keys: false, compact_arrays: false}
+    }
+}
+
+impl From<&StyleOptions> for Style {
+    fn from(value: &StyleOptions) -> Self {
+        Style {
+            indent: if value.tabs {Indent::Tabs} else {Indent::Spaces(value.indent.into())},
+            sort_keys: value.sort_keys,
+            compact_arrays: value.compact_arrays
+        }
+    }
+}
+
+/// Layout of streamed input, shared by the commands which can be streamed. Detected from the first character when omitted.
+#[derive(Debug, Default, Args)]
+pub struct StreamLayoutOptions {
+    #[arg(long, required = false, requires = "stream", conflicts_with = "array", help = "Read the streamed input as NDJSON, e.g. when every record is an array")]
+    pub ndjson: bool,
+
+    #[arg(long, required = false, requires = "stream", help = "Read the streamed input as a single top-level array")]
+    pub array: bool
+}
+
+impl StreamLayoutOptions {
+    pub fn layout(&self) -> Option<Layout> {
+        if self.ndjson {
+            Some(Layout::Values)
+        } else if self.array {
+            Some(Layout::Array)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Debug, Args)]
 pub struct MinifyOptions {
-    #[arg(long, short, value_name = "path to file", conflicts_with = "content")]
+    #[arg(long, short, value_name = "path to file", conflicts_with = "content", help = "Path to JSON file ('-' reads stdin)")]
     pub file: Option<String>,
 
-    #[arg(long, value_name = "json content", conflicts_with = "file")]
-    pub content: Option<String>
+    #[arg(long, value_name = "json content", conflicts_with = "file", help = "JSON content ('-' reads stdin). Stdin is used when neither file nor content is provided")]
+    pub content: Option<Input>,
+
+    #[arg(long, required = false, conflicts_with = "content", help = "Process NDJSON value by value, or a top-level array item by item, without loading the whole input")]
+    pub stream: bool,
+
+    #[command(flatten)]
+    pub layout: StreamLayoutOptions
 }
 
 #[derive(Debug, Args)]
 pub struct ValidateOptions {
-    #[arg(long, short, value_name = "path to file", conflicts_with = "content")]
+    #[arg(long, short, value_name = "path to file", conflicts_with = "content", help = "Path to JSON file ('-' reads stdin)")]
+    pub file: Option<String>,
+
+    #[arg(long, value_name = "json content", conflicts_with = "file", help = "JSON content ('-' reads stdin). Stdin is used when neither file nor content is provided")]
+    pub content: Option<Input>,
+
+    #[arg(long, short, value_name = "path to schema", required = false, help = "Also validate against a JSON Schema (only local $refs are resolved)")]
+    pub schema: Option<String>,
+
+    #[arg(long, required = false, requires = "schema", help = "JSON Schema draft. Taken from $schema when omitted, defaulting to 2020-12")]
+    pub draft: Option<JSONSchemaDraft>,
+
+    #[arg(long, required = false, conflicts_with = "content", help = "Process NDJSON value by value, or a top-level array item by item, without loading the whole input. The schema applies to every value")]
+    pub stream: bool,
+
+    #[command(flatten)]
+    pub layout: StreamLayoutOptions
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum JSONSchemaDraft {
+    #[value(name = "7")]
+    Draft7,
+    #[value(name = "2020-12")]
+    Draft202012
+}
+
+impl From<&JSONSchemaDraft> for SchemaDraft {
+    fn from(value: &JSONSchemaDraft) -> Self {
+        match value {
+            JSONSchemaDraft::Draft7 => SchemaDraft::Draft7,
+            JSONSchemaDraft::Draft202012 => SchemaDraft::Draft202012
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct QueryOptions {
+    #[arg(required = true, help = "jq expression, e.g. '.items[] | select(.price > 10) | {name, price}'")]
+    pub query: String,
+
+    #[arg(long, short, value_name = "path to file", conflicts_with = "content", help = "Path to JSON file ('-' reads stdin)")]
+    pub file: Option<String>,
+
+    #[arg(long, value_name = "json content", conflicts_with = "file", help = "JSON content ('-' reads stdin). Stdin is used when neither file nor content is provided")]
+    pub content: Option<Input>,
+
+    #[arg(long, short, required = false, help = "Print strings without quotes")]
+    pub raw_output: bool,
+
+    #[arg(long, short, required = false, help = "Print every result on a single line")]
+    pub compact: bool,
+
+    #[arg(long, required = false, conflicts_with = "content", help = "Process NDJSON value by value, or a top-level array item by item, without loading the whole input. The query runs on every value")]
+    pub stream: bool,
+
+    #[command(flatten)]
+    pub layout: StreamLayoutOptions
+}
+
+#[derive(Debug, Args)]
+pub struct GetOptions {
+    #[arg(required = true, help = "JSON Pointer (e.g. '/items/0/name') or JSONPath starting with '$' (e.g. '$.items[*].name')")]
+    pub path: String,
+
+    #[arg(long, short, value_name = "path to file", conflicts_with = "content", help = "Path to JSON file ('-' reads stdin)")]
+    pub file: Option<String>,
+
+    #[arg(long, value_name = "json content", conflicts_with = "file", help = "JSON content ('-' reads stdin). Stdin is used when neither file nor content is provided")]
+    pub content: Option<Input>,
+
+    #[arg(long, short, required = false, help = "Print strings without quotes")]
+    pub raw_output: bool
+}
+
+#[derive(Debug, Args)]
+pub struct SetOptions {
+    #[arg(required = true, help = "JSON Pointer, e.g. '/scripts/build'. Missing parent objects are created")]
+    pub pointer: String,
+
+    #[arg(required = true, allow_negative_numbers = true, help = "New value as JSON. Values which aren't valid JSON are used as strings")]
+    pub value: String,
+
+    #[arg(long, short, required = false, help = "Always use the value as a string")]
+    pub string: bool,
+
+    #[arg(long, short, value_name = "path to file", conflicts_with = "content", help = "Path to JSON file ('-' reads stdin)")]
     pub file: Option<String>,
 
-    #[arg(long, value_name = "json content", conflicts_with = "file")]
-    pub content: Option<String>
-}
\ No newline at end of file
+    #[arg(long, value_name = "json content", conflicts_with = "file", help = "JSON content ('-' reads stdin). Stdin is used when neither file nor content is provided")]
+    pub content: Option<Input>,
+
+    #[command(flatten)]
+    pub style: StyleOptions
+}
+
+#[derive(Debug, Args)]
+pub struct DeleteOptions {
+    #[arg(required = true, help = "JSON Pointer, e.g. '/metadata/annotations'")]
+    pub pointer: String,
+
+    #[arg(long, short, value_name = "path to file", conflicts_with = "content", help = "Path to JSON file ('-' reads stdin)")]
+    pub file: Option<String>,
+
+    #[arg(long, value_name = "json content", conflicts_with = "file", help = "JSON content ('-' reads stdin). Stdin is used when neither file nor content is provided")]
+    pub content: Option<Input>,
+
+    #[command(flatten)]
+    pub style: StyleOptions
+}
+
+#[derive(Debug, Args)]
+pub struct DiffOptions {
+    #[arg(required = true, value_name = "old file", help = "Path to the original JSON file ('-' reads stdin)")]
+    pub old: String,
+
+    #[arg(required = true, value_name = "new file", help = "Path to the changed JSON file ('-' reads stdin)")]
+    pub new: String,
+
+    #[arg(long, required = false, help = "Compare arrays regardless of the order of their items")]
+    pub ignore_order: bool,
+
+    #[arg(long, short, value_name = "key", required = false, help = "Ignore object keys with this name, or the location if it starts with '/'. Can be repeated")]
+    pub ignore_key: Vec<String>,
+
+    #[arg(long, short, required = false, help = "Print the differences as an RFC 6902 JSON Patch")]
+    pub patch: bool
+}
+
+#[derive(Debug, Args)]
+pub struct PatchOptions {
+    #[arg(required = true, value_name = "document", help = "Path to the JSON file to patch ('-' reads stdin)")]
+    pub document: String,
+
+    #[arg(required = true, value_name = "patch", help = "Path to the patch file ('-' reads stdin)")]
+    pub patch: String,
+
+    #[arg(long, short, required = false, help = "Treat the patch as an RFC 7396 JSON Merge Patch instead of an RFC 6902 JSON Patch")]
+    pub merge: bool,
+
+    #[arg(long, short = 'n', required = false, help = "Print the patched document instead of writing it back to the file")]
+    pub dry_run: bool,
+
+    #[command(flatten)]
+    pub style: StyleOptions
+}
+
+#[derive(Debug, Args)]
+pub struct ToCsvOptions {
+    #[arg(long, short, value_name = "path to file", conflicts_with = "content", help = "Path to JSON file ('-' reads stdin)")]
+    pub file: Option<String>,
+
+    #[arg(long, value_name = "json content", conflicts_with = "file", help = "JSON content ('-' reads stdin). Stdin is used when neither file nor content is provided")]
+    pub content: Option<Input>,
+
+    #[arg(long, short, required = false, default_value = ",", help = "Field delimiter (e.g. ';' or $'\\t')")]
+    pub delimiter: char,
+
+    #[arg(long, short, value_name = "columns", required = false, value_delimiter = ',', conflicts_with = "sort_columns", help = "Comma separated columns to write, in this order (e.g. 'id,user.name')")]
+    pub columns: Vec<String>,
+
+    #[arg(long, required = false, help = "Sort the columns alphabetically instead of keeping their order of appearance")]
+    pub sort_columns: bool,
+
+    #[arg(long, short, required = false, default_value = "index", help = "How nested arrays are written")]
+    pub arrays: CsvArrayMode
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CsvArrayMode {
+    #[value(help = "One column per item, e.g. 'tags.0', 'tags.1'")]
+    Index,
+    #[value(help = "Scalar items joined with ';' in one column")]
+    Join,
+    #[value(help = "The array as JSON in one column")]
+    Json
+}
+
+impl From<CsvArrayMode> for ArrayMode {
+    fn from(value: CsvArrayMode) -> Self {
+        match value {
+            CsvArrayMode::Index => ArrayMode::Index,
+            CsvArrayMode::Join => ArrayMode::Join,
+            CsvArrayMode::Json => ArrayMode::Json
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct CanonicalizeOptions {
+    #[arg(long, short, value_name = "path to file", conflicts_with = "content", help = "Path to JSON file ('-' reads stdin)")]
+    pub file: Option<String>,
+
+    #[arg(long, value_name = "json content", conflicts_with = "file", help = "JSON content ('-' reads stdin). Stdin is used when neither file nor content is provided")]
+    pub content: Option<Input>
+}
+
+#[derive(Debug, Args)]
+pub struct JSONHashOptions {
+    #[arg(long, short, value_name = "path to file", conflicts_with = "content", help = "Path to JSON file ('-' reads stdin)")]
+    pub file: Option<String>,
+
+    #[arg(long, value_name = "json content", conflicts_with = "file", help = "JSON content ('-' reads stdin). Stdin is used when neither file nor content is provided")]
+    pub content: Option<Input>,
+
+    #[arg(long, short, required = false, help = "Hash algorithm", default_value = "sha256")]
+    pub algorithm: HashAlgorithm
+}
+
+#[derive(Debug, Args)]
+pub struct TypesOptions {
+    #[arg(value_name = "sample files", help = "Paths to sample JSON files ('-' reads stdin). Stdin is used when none are provided. Arrays are read as lists of samples")]
+    pub files: Vec<String>,
+
+    #[arg(long, short, required = true, help = "Language of the generated types")]
+    pub lang: TypesLanguage,
+
+    #[arg(long, short, required = false, default_value = "Root", help = "Name of the root type")]
+    pub name: String
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum TypesLanguage {
+    #[value(alias = "rs", help = "Structs with serde derives")]
+    Rust,
+    #[value(alias = "ts", help = "Interfaces")]
+    Typescript,
+    #[value(help = "Structs with json tags")]
+    Go,
+    #[value(alias = "py", help = "Dataclasses")]
+    Python
+}
+
+impl From<TypesLanguage> for Language {
+    fn from(value: TypesLanguage) -> Self {
+        match value {
+            TypesLanguage::Rust => Language::Rust,
+            TypesLanguage::Typescript => Language::TypeScript,
+            TypesLanguage::Go => Language::Go,
+            TypesLanguage::Python => Language::Python
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct SchemaInferOptions {
+    #[arg(value_name = "sample files", help = "Paths to sample JSON files ('-' reads stdin). Stdin is used when none are provided")]
+    pub files: Vec<String>,
+
+    #[command(flatten)]
+    pub style: StyleOptions
+}
+
+#[derive(Debug, Args)]
+pub struct FlattenOptions {
+    #[arg(long, short, value_name = "path to file", conflicts_with = "content", help = "Path to JSON file ('-' reads stdin)")]
+    pub file: Option<String>,
+
+    #[arg(long, value_name = "json content", conflicts_with = "file", help = "JSON content ('-' reads stdin). Stdin is used when neither file nor content is provided")]
+    pub content: Option<Input>,
+
+    #[command(flatten)]
+    pub paths: PathOptions,
+
+    #[arg(long, short, required = false, help = "Print 'path=value' lines with strings unquoted instead of a JSON object")]
+    pub lines: bool,
+
+    #[command(flatten)]
+    pub style: StyleOptions
+}
+
+#[derive(Debug, Args)]
+pub struct UnflattenOptions {
+    #[arg(long, short, value_name = "path to file", conflicts_with = "content", help = "Path to JSON file ('-' reads stdin)")]
+    pub file: Option<String>,
+
+    #[arg(long, value_name = "json content", conflicts_with = "file", help = "JSON content ('-' reads stdin). Stdin is used when neither file nor content is provided")]
+    pub content: Option<Input>,
+
+    #[command(flatten)]
+    pub paths: PathOptions,
+
+    #[command(flatten)]
+    pub style: StyleOptions
+}
+
+/// How the key paths of `flatten` and `unflatten` are written
+#[derive(Debug, Args)]
+pub struct PathOptions {
+    #[arg(long, short, required = false, default_value = ".", help = "Separator between object keys (e.g. '__' for env vars)")]
+    pub separator: String,
+
+    #[arg(long, short, required = false, default_value = "brackets", help = "How array indices are written")]
+    pub arrays: PathArrayNotation
+}
+
+impl From<&PathOptions> for PathStyle {
+    fn from(value: &PathOptions) -> Self {
+        PathStyle {separator: value.separator.clone(), arrays: value.arrays.into()}
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum PathArrayNotation {
+    #[value(help = "'tags[0]'")]
+    Brackets,
+    #[value(help = "'tags.0' with the separator. Numeric keys are read back as array indices")]
+    Index
+}
+
+impl From<PathArrayNotation> for ArrayNotation {
+    fn from(value: PathArrayNotation) -> Self {
+        match value {
+            PathArrayNotation::Brackets => ArrayNotation::Brackets,
+            PathArrayNotation::Index => ArrayNotation::Index
+        }
+    }
+}
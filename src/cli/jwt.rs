@@ -1,5 +1,6 @@
 use clap::{Args, Subcommand, ValueEnum};
-use jsonwebtoken::Algorithm;
+use dx::jwt::Algorithm;
+use crate::input::Input;
 
 #[derive(Debug, Subcommand)]
 pub enum JWTMethod {
@@ -17,14 +18,14 @@ pub enum JWTMethod {
 
 #[derive(Debug, Args)]
 pub struct JWTDecodeOptions {
-    #[arg(required = true, help = "JWT token string")]
-    pub token: String
+    #[arg(default_value = "-", hide_default_value = true, help = "JWT token string (reads stdin when omitted or '-')")]
+    pub token: Input
 }
 
 #[derive(Debug, Args)]
 pub struct JWTVerifyOptions {
-    #[arg(required = true, help = "JWT token string")]
-    pub token: String,
+    #[arg(required = true, help = "JWT token string ('-' reads stdin)")]
+    pub token: Input,
 
     #[arg(required = true, help = "JWT token secret")]
     pub secret: String,
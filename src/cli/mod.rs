@@ -1,31 +1,55 @@
-mod json;
+pub mod json;
+pub mod convert;
+mod csv;
 mod encode_decode;
 mod uuid;
 mod time;
 mod text;
 mod hash;
-mod jwt;
+pub mod jwt;
 mod regex;
 mod lorem;
+mod pipe;
+mod generate;
+mod config;
 
-use clap::{Parser, Subcommand};
-pub use crate::cli::json::{JSONMethod, FormatOptions, MinifyOptions, ValidateOptions};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+pub use crate::cli::json::{JSONMethod, FormatOptions, MinifyOptions, ValidateOptions, QueryOptions, GetOptions, SetOptions, DeleteOptions, DiffOptions, PatchOptions, ToCsvOptions, StyleOptions, CanonicalizeOptions, JSONHashOptions, TypesOptions, SchemaMethod, SchemaInferOptions, FlattenOptions, UnflattenOptions};
+pub use crate::cli::convert::{ConvertOptions};
+pub use crate::cli::csv::{CsvMethod, CsvToJsonOptions};
 pub use crate::cli::encode_decode::{EncodeMethod, DecodeMethod, Base64Options, URLOptions, HexOptions};
 pub use crate::cli::hash::{HashMethod, HashFileOptions, HashVerifyOptions, HashAlgorithm};
 pub use crate::cli::uuid::{UuidMethod, UuidOptions};
 pub use crate::cli::time::{TimeMethod, TimeNowOptions, TimeFromUnixOptions, TimeToUnixOptions, TimeAgoOptions, TimeFormatOptions};
-pub use crate::cli::text::{TextMethod};
-pub use crate::cli::jwt::{JWTMethod, JWTDecodeOptions, JWTVerifyOptions, JWTAlgorithm};
+pub use crate::cli::text::{TextMethod, TextOptions};
+pub use crate::cli::jwt::{JWTMethod, JWTDecodeOptions, JWTVerifyOptions};
 pub use crate::cli::regex::{RegexMethod, RegexTestOptions, RegexMatchOptions, RegexReplaceOptions};
 pub use crate::cli::lorem::{LoremMethod, LoremOptions};
+pub use crate::cli::pipe::{PipeOptions};
+pub use crate::cli::generate::{CompletionsOptions, ManOptions};
+pub use crate::cli::config::{ConfigMethod, ConfigGetOptions, ConfigSetOptions};
 
 #[derive(Debug, Subcommand)]
 pub enum Command {
     #[command(about = "Format, validate, and manipulate JSON data")]
     Json {
+        #[arg(long, visible_alias = "json5", global = true, required = false, help = "Accept comments, trailing commas, single quotes and unquoted keys (JSON5). The output is always strict JSON")]
+        lenient: bool,
+
         #[command(subcommand)]
         method: JSONMethod
     },
+    #[command(about = "Convert between JSON, YAML, TOML, JSON5 and NDJSON")]
+    Convert {
+        #[command(flatten)]
+        options: ConvertOptions
+    },
+    #[command(about = "Convert CSV data")]
+    Csv {
+        #[command(subcommand)]
+        method: CsvMethod
+    },
     #[command(about = "Encode data to various formats")]
     Encode {
         #[command(subcommand)]
@@ -70,15 +94,84 @@ pub enum Command {
     Lorem {
         #[command(subcommand)]
         method: LoremMethod
+    },
+    #[command(about = "Chain operations, feeding the result of each one as input to the next")]
+    Pipe {
+        #[command(flatten)]
+        options: PipeOptions
+    },
+    #[command(about = "Start an interactive shell")]
+    Shell,
+    #[command(about = "Generate a shell completion script")]
+    Completions {
+        #[command(flatten)]
+        options: CompletionsOptions
+    },
+    #[command(about = "Generate man pages")]
+    Man {
+        #[command(flatten)]
+        options: ManOptions
+    },
+    #[command(about = "Show and edit the configuration file")]
+    Config {
+        #[command(subcommand)]
+        method: ConfigMethod
     }
 }
 
+const EXIT_CODES_HELP: &str = "Exit codes:
+  0  Success
+  1  Negative result (no match, hash or JWT verification failed)
+  2  Usage error or missing arguments
+  3  Runtime or IO error";
+
 #[derive(Debug, Parser)]
-#[command(author, version, about)]
+#[command(author, version, about, after_help = EXIT_CODES_HELP)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Command,
 
     #[arg(long, short, global = true, required = false, help = "Path to file where to save the results")]
-    pub output: Option<String>
+    pub output: Option<String>,
+
+    #[arg(long, visible_alias = "plain", global = true, required = false, help = "Print only the result without banners (default when stdout is not a terminal)")]
+    pub raw: bool,
+
+    #[arg(long, global = true, required = false, default_value = "text", help = "Format of the printed results")]
+    pub output_format: OutputFormat,
+
+    #[arg(long, global = true, required = false, default_value = "auto", help = "When to use colours")]
+    pub color: ColorMode,
+
+    #[arg(long, global = true, required = false, help = "Run the operation for every line of stdin, printing one result per line")]
+    pub each_line: bool,
+
+    #[arg(long, global = true, required = false, value_name = "FILE", conflicts_with = "each_line", help = "Run the operation for every line of the file, printing one result per line")]
+    pub batch: Option<String>
+}
+
+/// Parser for a single operation, without the binary name and global options. Used by `dx pipe` and `dx shell`
+#[derive(Debug, Parser)]
+#[command(name = "dx", no_binary_name = true, about)]
+pub struct CommandLine {
+    #[command(subcommand)]
+    pub command: Command
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Human readable text
+    Text,
+    /// Structured JSON
+    Json
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// Colours unless NO_COLOR is set or the output is piped
+    Auto,
+    Always,
+    Never
 }
\ No newline at end of file
@@ -0,0 +1,14 @@
+use clap::Args;
+
+#[derive(Debug, Args)]
+pub struct PipeOptions {
+    #[arg(
+        required = true,
+        num_args = 1..,
+        trailing_var_arg = true,
+        allow_hyphen_values = true,
+        value_name = "OPERATIONS",
+        help = "Operations separated by '|', e.g. \"decode base64 | json format | text upper\""
+    )]
+    pub stages: Vec<String>
+}
@@ -1,4 +1,5 @@
 use clap::{Args, Subcommand};
+use crate::input::Input;
 
 #[derive(Debug, Subcommand)]
 pub enum RegexMethod {
@@ -24,8 +25,8 @@ pub struct RegexTestOptions {
     #[arg(required = true, help = "RegEx pattern")]
     pub pattern: String,
 
-    #[arg(required = true)]
-    pub text: String
+    #[arg(default_value = "-", hide_default_value = true, help = "Text to search (reads stdin when omitted or '-')")]
+    pub text: Input
 }
 
 #[derive(Debug, Args)]
@@ -33,8 +34,8 @@ pub struct RegexMatchOptions {
     #[arg(required = true, help = "RegEx pattern")]
     pub pattern: String,
 
-    #[arg(required = true)]
-    pub text: String
+    #[arg(default_value = "-", hide_default_value = true, help = "Text to search (reads stdin when omitted or '-')")]
+    pub text: Input
 }
 
 #[derive(Debug, Args)]
@@ -45,6 +46,6 @@ pub struct RegexReplaceOptions {
     #[arg(required = true, help = "Text which to place in the place of the matches")]
     pub replacement: String,
 
-    #[arg(required = true)]
-    pub text: String,
+    #[arg(default_value = "-", hide_default_value = true, help = "Text to search (reads stdin when omitted or '-')")]
+    pub text: Input,
 }
\ No newline at end of file
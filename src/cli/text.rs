@@ -1,4 +1,5 @@
 use clap::{Args, Subcommand};
+use crate::input::Input;
 
 #[derive(Debug, Subcommand)]
 pub enum TextMethod {
@@ -41,6 +42,6 @@ pub enum TextMethod {
 
 #[derive(Debug, Args)]
 pub struct TextOptions {
-    #[arg(required = true, help = "Text to transform")]
-    pub text: String
+    #[arg(default_value = "-", hide_default_value = true, help = "Text to transform (reads stdin when omitted or '-')")]
+    pub text: Input
 }
\ No newline at end of file
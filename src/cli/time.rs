@@ -1,4 +1,8 @@
-use clap::{Args, Subcommand};
+use std::ffi::OsStr;
+use clap::builder::{PossibleValue, StringValueParser, TypedValueParser};
+use clap::{Arg, Args, Subcommand};
+use dx::time::TZ_VARIANTS;
+use crate::input::Input;
 
 #[derive(Debug, Subcommand)]
 pub enum TimeMethod {
@@ -35,23 +39,23 @@ pub enum TimeMethod {
 
 #[derive(Debug, Args)]
 pub struct TimeNowOptions {
-    #[arg(long, short, required = false, help = "Timezone")]
+    #[arg(long, short, required = false, value_parser = TimezoneParser, hide_possible_values = true, help = "Timezone (run 'dx time tz' to list all)")]
     pub timezone: Option<String>
 }
 
 #[derive(Debug, Args)]
 pub struct TimeFromUnixOptions {
-    #[arg(required = true, help = "Unix Timestamp")]
-    pub timestamp: i64,
+    #[arg(default_value = "-", hide_default_value = true, allow_negative_numbers = true, help = "Unix Timestamp (reads stdin when omitted or '-')")]
+    pub timestamp: Input,
 
-    #[arg(long, short, required = false, help = "Timezone")]
+    #[arg(long, short, required = false, value_parser = TimezoneParser, hide_possible_values = true, help = "Timezone (run 'dx time tz' to list all)")]
     pub timezone: Option<String>
 }
 
 #[derive(Debug, Args)]
 pub struct TimeToUnixOptions {
-    #[arg(required = true, help = "Date string")]
-    pub date: String,
+    #[arg(default_value = "-", hide_default_value = true, help = "Date string (reads stdin when omitted or '-')")]
+    pub date: Input,
 
     #[arg(long, short, required = true, help = "strftime/strptime date format")]
     pub format: String
@@ -59,8 +63,8 @@ pub struct TimeToUnixOptions {
 
 #[derive(Debug, Args)]
 pub struct TimeAgoOptions {
-    #[arg(required = true, help = "Unix Timestamp")]
-    pub timestamp: i64,
+    #[arg(default_value = "-", hide_default_value = true, allow_negative_numbers = true, help = "Unix Timestamp (reads stdin when omitted or '-')")]
+    pub timestamp: Input,
 
     #[arg(long, short, required = false, help = "Start timestamp")]
     pub start: Option<i64>
@@ -68,12 +72,28 @@ pub struct TimeAgoOptions {
 
 #[derive(Debug, Args)]
 pub struct TimeFormatOptions {
-    #[arg(required = true, help = "Date string")]
-    pub date: String,
+    #[arg(default_value = "-", hide_default_value = true, help = "Date string (reads stdin when omitted or '-')")]
+    pub date: Input,
 
     #[arg(long, short, required = true, help = "Original date format (strftime/strptime)")]
     pub from: String,
 
     #[arg(long, short, required = true, help = "Target date format (strftime/strptime)")]
     pub target: String
-}
\ No newline at end of file
+}
+
+/// Advertises all known timezone names for shell completions. The validation itself happens when the timezone is used.
+#[derive(Clone)]
+struct TimezoneParser;
+
+impl TypedValueParser for TimezoneParser {
+    type Value = String;
+
+    fn parse_ref(&self, cmd: &clap::Command, arg: Option<&Arg>, value: &OsStr) -> Result<String, clap::Error> {
+        StringValueParser::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(TZ_VARIANTS.iter().map(|tz| PossibleValue::new(tz.name()))))
+    }
+}
@@ -0,0 +1,276 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::{env, fs, result};
+use clap::{Command, CommandFactory, FromArgMatches, ValueEnum};
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+use toml_edit::DocumentMut;
+use crate::cli::{Cli, ColorMode, CommandLine, HashAlgorithm, OutputFormat};
+use crate::handlers::CommandHandlerError;
+
+/// Name of the project-local config file, searched for in the current directory and its parents
+pub const PROJECT_FILE: &str = ".dx.toml";
+
+/// All supported settings in the form `section.name`
+pub const KEYS: [&str; 6] = [
+    "output.format",
+    "output.color",
+    "time.timezone",
+    "hash.algorithm",
+    "base64.url_safe",
+    "base64.no_padding"
+];
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub output: OutputSettings,
+    pub time: TimeSettings,
+    pub hash: HashSettings,
+    pub base64: Base64Settings
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputSettings {
+    pub format: Option<OutputFormat>,
+    pub color: Option<ColorMode>
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TimeSettings {
+    pub timezone: Option<String>
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HashSettings {
+    pub algorithm: Option<HashAlgorithm>
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Base64Settings {
+    pub url_safe: Option<bool>,
+    pub no_padding: Option<bool>
+}
+
+impl Settings {
+    fn from_table(table: &Table) -> result::Result<Settings, String> {
+        let settings = Value::Table(table.clone()).try_into::<Settings>()
+            .map_err(|err| err.to_string().trim_end().to_string())?;
+
+        if let Some(timezone) = &settings.time.timezone {
+            dx::time::parse_timezone(timezone).map_err(|err| err.to_string())?;
+        }
+        Ok(settings)
+    }
+}
+
+/// The merged configuration. Values from the project-local file override the user config.
+#[derive(Debug, Default)]
+pub struct Config {
+    pub settings: Settings,
+    pub values: Table,
+    pub files: Vec<PathBuf>
+}
+
+impl Config {
+    pub fn load() -> result::Result<Config, CommandHandlerError> {
+        let mut values = Table::new();
+        let mut files = Vec::new();
+        for path in [user_path(), project_path()].into_iter().flatten() {
+            if !path.is_file() {
+                continue;
+            }
+            merge(&mut values, read_file(&path)?);
+            files.push(path);
+        }
+
+        let mut config = Config::from_table(values)?;
+        config.files = files;
+        Ok(config)
+    }
+
+    pub fn from_table(values: Table) -> result::Result<Config, CommandHandlerError> {
+        let settings = Settings::from_table(&values)
+            .map_err(|err| CommandHandlerError::InvalidArguments(format!("The configuration is invalid! {err}")))?;
+        Ok(Config {settings, values, files: Vec::new()})
+    }
+
+    /// Looks up a `section.name` key
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        let (section, name) = key.split_once('.')?;
+        self.values.get(section)?.as_table()?.get(name)
+    }
+
+    /// Uses the configured values as defaults of the matching arguments, so flags passed on the command line still win
+    pub fn apply_defaults(&self, mut command: Command) -> Command {
+        let settings = &self.settings;
+        if let Some(timezone) = &settings.time.timezone {
+            for method in ["now", "from-unix"] {
+                command = set_default(command, &["time", method], "timezone", timezone.clone());
+            }
+        }
+        if let Some(algorithm) = &settings.hash.algorithm {
+            for path in [["hash", "file"], ["hash", "verify"], ["json", "hash"]] {
+                command = set_default(command, &path, "algorithm", value_name(algorithm));
+            }
+        }
+        for (arg, value) in [("url_safe", settings.base64.url_safe), ("no_padding", settings.base64.no_padding)] {
+            if let Some(value) = value {
+                for method in ["encode", "decode"] {
+                    command = set_default(command, &[method, "base64"], arg, value.to_string());
+                }
+            }
+        }
+        command
+    }
+
+    /// Same as `apply_defaults`, including the global output options
+    pub fn apply_global_defaults(&self, mut command: Command) -> Command {
+        if let Some(format) = &self.settings.output.format {
+            command = set_default(command, &[], "output_format", value_name(format));
+        }
+        if let Some(color) = &self.settings.output.color {
+            command = set_default(command, &[], "color", value_name(color));
+        }
+        self.apply_defaults(command)
+    }
+}
+
+/// Loads the configuration once. Later calls of `get` return it, or the defaults if loading failed.
+pub fn init() -> result::Result<&'static Config, CommandHandlerError> {
+    let config = Config::load()?;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// The loaded configuration, or the defaults if `init` wasn't called
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+/// Parses the process arguments with the configured defaults
+pub fn parse_cli() -> Cli {
+    let matches = get().apply_global_defaults(Cli::command()).get_matches();
+    Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit())
+}
+
+/// Parses a single operation with the configured defaults. Used by `dx pipe` and `dx shell`
+pub fn parse_command_line<I, T>(args: I) -> result::Result<CommandLine, clap::Error> where I: IntoIterator<Item = T>, T: Into<OsString> + Clone {
+    let matches = get().apply_defaults(CommandLine::command()).try_get_matches_from(args)?;
+    CommandLine::from_arg_matches(&matches)
+}
+
+/// `$XDG_CONFIG_HOME/dx/config.toml`, or `~/.config/dx/config.toml` on every platform when it isn't set
+pub fn user_path() -> Option<PathBuf> {
+    user_path_in(env::var_os("XDG_CONFIG_HOME"), dirs::home_dir())
+}
+
+fn user_path_in(xdg_config_home: Option<OsString>, home: Option<PathBuf>) -> Option<PathBuf> {
+    // Relative paths are invalid in the XDG spec and have to be ignored
+    let config_dir = xdg_config_home.map(PathBuf::from).filter(|dir| dir.is_absolute())
+        .or_else(|| home.map(|home| home.join(".config")))?;
+    Some(config_dir.join("dx").join("config.toml"))
+}
+
+pub fn project_path() -> Option<PathBuf> {
+    env::current_dir().ok()?
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Reads and validates a single config file
+pub fn read_file(path: &Path) -> result::Result<Table, CommandHandlerError> {
+    let table = read_to_string(path)?.parse::<Table>().map_err(|err| invalid_file(path, err.to_string().trim_end().to_string()))?;
+    Settings::from_table(&table).map_err(|err| invalid_file(path, err))?;
+    Ok(table)
+}
+
+/// Reads a config file for editing without checking the settings, so `dx config set` can replace invalid values.
+/// Comments, formatting and key order are kept.
+pub fn parse_document(path: &Path) -> result::Result<DocumentMut, CommandHandlerError> {
+    read_to_string(path)?.parse::<DocumentMut>().map_err(|err| invalid_file(path, err.to_string().trim_end().to_string()))
+}
+
+fn read_to_string(path: &Path) -> result::Result<String, CommandHandlerError> {
+    fs::read_to_string(path)
+        .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to read the config file '{}'! {err}", path.display()))))
+}
+
+fn invalid_file(path: &Path, err: String) -> CommandHandlerError {
+    CommandHandlerError::InvalidArguments(format!("The config file '{}' is invalid! {err}", path.display()))
+}
+
+fn merge(base: &mut Table, other: Table) {
+    for (key, value) in other {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(other)) => merge(base, other),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn set_default(command: Command, path: &[&str], arg: &str, value: String) -> Command {
+    match path {
+        [] => command.mut_arg(arg, |arg| arg.default_value(value)),
+        [name, rest @ ..] => command.mut_subcommand(name, |subcommand| set_default(subcommand, rest, arg, value))
+    }
+}
+
+fn value_name(value: &impl ValueEnum) -> String {
+    value.to_possible_value().map(|value| value.get_name().to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use crate::cli::{Command as CliCommand, HashMethod};
+    use crate::utils::TestResult;
+    use super::*;
+
+    fn algorithm(config: &Config, args: &[&str]) -> Option<HashAlgorithm> {
+        let matches = config.apply_defaults(CommandLine::command()).try_get_matches_from(args).ok()?;
+        match CommandLine::from_arg_matches(&matches).ok()?.command {
+            CliCommand::Hash {method: HashMethod::File {options}} => Some(options.algorithm),
+            _ => None
+        }
+    }
+
+    #[test]
+    fn cli_flags_override_config() -> TestResult {
+        let config = Config::from_table("[hash]\nalgorithm = \"sha512\"".parse::<Table>().map_err(|err| err.to_string())?)?;
+
+        assert!(matches!(algorithm(&config, &["hash", "file", "Cargo.toml"]), Some(HashAlgorithm::Sha512)));
+        assert!(matches!(algorithm(&config, &["hash", "file", "Cargo.toml", "-a", "md5"]), Some(HashAlgorithm::Md5)));
+        Ok(())
+    }
+
+    #[test]
+    fn project_values_override_user_values() -> TestResult {
+        let mut values = "[time]\ntimezone = \"UTC\"\n[hash]\nalgorithm = \"md5\"".parse::<Table>().map_err(|err| err.to_string())?;
+        merge(&mut values, "[time]\ntimezone = \"Europe/Sofia\"".parse::<Table>().map_err(|err| err.to_string())?);
+        let config = Config::from_table(values)?;
+
+        assert_eq!(config.settings.time.timezone.as_deref(), Some("Europe/Sofia"));
+        assert_eq!(config.get("hash.algorithm").and_then(Value::as_str), Some("md5"));
+        assert!(Config::from_table("[time]\ntimezone = \"Mars/Olympus\"".parse::<Table>().map_err(|err| err.to_string())?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn user_config_in_xdg_config_home() {
+        let home = Some(PathBuf::from("/home/ann"));
+        assert_eq!(user_path_in(Some(OsString::from("/etc/xdg")), home.clone()), Some(PathBuf::from("/etc/xdg/dx/config.toml")));
+        assert_eq!(user_path_in(None, home.clone()), Some(PathBuf::from("/home/ann/.config/dx/config.toml")));
+        assert_eq!(user_path_in(Some(OsString::from("relative")), home), Some(PathBuf::from("/home/ann/.config/dx/config.toml")));
+        assert_eq!(user_path_in(None, None), None);
+    }
+}
@@ -0,0 +1,193 @@
+//! Converting documents between JSON, YAML, TOML, JSON5 and NDJSON through `serde_json::Value`
+
+use std::path::Path;
+use miette::SourceSpan;
+use serde_json::{Map, Number, Value};
+use crate::json::{self, JSONParseError};
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DataFormat {
+    Json,
+    Yaml,
+    Toml,
+    Json5,
+    /// Newline delimited JSON, one value per line. Read as an array of the values
+    Ndjson
+}
+
+impl DataFormat {
+    /// Guesses the format from the file extension
+    pub fn from_path(path: &str) -> Option<DataFormat> {
+        let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "json" => Some(DataFormat::Json),
+            "yaml" | "yml" => Some(DataFormat::Yaml),
+            "toml" => Some(DataFormat::Toml),
+            "json5" => Some(DataFormat::Json5),
+            "ndjson" | "jsonl" => Some(DataFormat::Ndjson),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DataFormat::Json => "JSON",
+            DataFormat::Yaml => "YAML",
+            DataFormat::Toml => "TOML",
+            DataFormat::Json5 => "JSON5",
+            DataFormat::Ndjson => "NDJSON"
+        }
+    }
+}
+
+/// Parses a document. `src_name` is the name of the source (e.g. file path) shown in diagnostics.
+pub fn parse(src: &str, format: DataFormat, src_name: &str) -> Result<Value> {
+    let syntax_error = |offset: usize, message: String| -> Error {
+        let offset = offset.min(src.len());
+        // An empty span still gets a label at the end of a line or of the source
+        let length = src.get(offset..)
+            .and_then(|rest| rest.chars().next())
+            .filter(|char| *char != '\n' && *char != '\r')
+            .map_or(0, char::len_utf8);
+        JSONParseError::new(
+            src_name,
+            src.to_string(),
+            SourceSpan::new(offset.into(), length),
+            format!("Failed to parse {}: {message}", format.name())
+        ).into()
+    };
+
+    match format {
+        DataFormat::Json => json::parse(src, src_name),
+        DataFormat::Yaml => {
+            let value = serde_yaml_ng::from_str(src)
+                .map_err(|err| syntax_error(err.location().map_or(0, |location| location.index()), err.to_string()))?;
+            // `Value` would turn `.nan` and `.inf` into `null`, and `serde_yaml_ng::Value` loses how keys like `0x10`
+            // are written, so the numbers are checked on a second parse
+            if let Ok(yaml) = serde_yaml_ng::from_str::<serde_yaml_ng::Value>(src) {
+                check_yaml_numbers(&yaml, "").map_err(|err| Error::InvalidInput(format!("Failed to convert {} to JSON! {err}", format.name())))?;
+            }
+            Ok(value)
+        },
+        DataFormat::Toml => {
+            let table = src.parse::<toml::Table>()
+                .map_err(|err| syntax_error(err.span().map_or(0, |span| span.start), err.message().to_string()))?;
+            from_toml(toml::Value::Table(table), "").map_err(|err| Error::InvalidInput(format!("Failed to convert {} to JSON! {err}", format.name())))
+        },
+        DataFormat::Json5 => json::parse_lenient(src, src_name),
+        DataFormat::Ndjson => {
+            let mut values = Vec::new();
+            let mut line_start = 0;
+            for line in src.split_inclusive('\n') {
+                if !line.trim().is_empty() {
+                    // The position in the message is relative to the line, so only the label shows it
+                    let value = serde_json::from_str(line).map_err(|err| {
+                        let message = err.to_string();
+                        let message = message.split(" at line ").next().unwrap_or_default().to_string();
+                        syntax_error(line_start + err.column().saturating_sub(1), message)
+                    })?;
+                    values.push(value);
+                }
+                line_start += line.len();
+            }
+            Ok(Value::Array(values))
+        }
+    }
+}
+
+/// Renders the value in the format. JSON and JSON5 are pretty printed, NDJSON writes every item of an array on its own line.
+pub fn to_string(value: &Value, format: DataFormat) -> Result<String> {
+    let failed = |err: String| Error::InvalidInput(format!("Failed to convert to {}! {err}", format.name()));
+    match format {
+        // Every JSON document is also a valid JSON5 document
+        DataFormat::Json | DataFormat::Json5 => json::to_pretty(value),
+        DataFormat::Yaml => serde_yaml_ng::to_string(value).map_err(|err| failed(err.to_string())),
+        DataFormat::Toml => {
+            let table = to_toml(value).map_err(failed)?;
+            match table {
+                toml::Value::Table(table) => toml::to_string(&table).map_err(|err| failed(err.to_string())),
+                _ => Err(failed(String::from("A TOML document must be a table, but the value is not an object")))
+            }
+        },
+        DataFormat::Ndjson => {
+            let lines = match value {
+                Value::Array(items) => items.iter().map(json::minify).collect::<Result<Vec<String>>>()?,
+                value => vec![json::minify(value)?]
+            };
+            Ok(lines.join("\n"))
+        }
+    }
+}
+
+/// Dates and times become strings in RFC 3339 format. JSON has no `nan` and `inf`, so they can't be converted.
+/// `path` is the location of the value, e.g. `server.ports[0]`, named in the error.
+fn from_toml(value: toml::Value, path: &str) -> std::result::Result<Value, String> {
+    let converted = match value {
+        toml::Value::String(text) => Value::String(text),
+        toml::Value::Integer(number) => Value::Number(number.into()),
+        toml::Value::Float(number) => Value::Number(finite(number, path)?),
+        toml::Value::Boolean(bool) => Value::Bool(bool),
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter()
+            .enumerate()
+            .map(|(index, item)| from_toml(item, &format!("{path}[{index}]")))
+            .collect::<std::result::Result<Vec<Value>, String>>()?),
+        toml::Value::Table(table) => Value::Object(table.into_iter()
+            .map(|(key, value)| {
+                let value = from_toml(value, &child_path(path, &key))?;
+                Ok((key, value))
+            })
+            .collect::<std::result::Result<Map<String, Value>, String>>()?)
+    };
+    Ok(converted)
+}
+
+/// Same check as for TOML: JSON has no `.nan` and `.inf`, which serde would silently turn into `null`
+fn check_yaml_numbers(value: &serde_yaml_ng::Value, path: &str) -> std::result::Result<(), String> {
+    match value {
+        serde_yaml_ng::Value::Number(number) => match number.as_f64() {
+            Some(float) if number.is_f64() => finite(float, path).map(|_| ()),
+            _ => Ok(())
+        },
+        serde_yaml_ng::Value::Sequence(items) => items.iter()
+            .enumerate()
+            .try_for_each(|(index, item)| check_yaml_numbers(item, &format!("{path}[{index}]"))),
+        serde_yaml_ng::Value::Mapping(mapping) => mapping.iter().try_for_each(|(key, value)| {
+            let key = match key {
+                serde_yaml_ng::Value::String(key) => key.clone(),
+                key => serde_yaml_ng::to_string(key).unwrap_or_default().trim_end().to_string()
+            };
+            check_yaml_numbers(value, &child_path(path, &key))
+        }),
+        serde_yaml_ng::Value::Tagged(tagged) => check_yaml_numbers(&tagged.value, path),
+        _ => Ok(())
+    }
+}
+
+fn finite(number: f64, path: &str) -> std::result::Result<Number, String> {
+    Number::from_f64(number).ok_or_else(|| format!("JSON doesn't support {number} numbers, found at '{path}'"))
+}
+
+fn child_path(path: &str, key: &str) -> String {
+    if path.is_empty() {key.to_string()} else {format!("{path}.{key}")}
+}
+
+/// TOML has no `null` and its integers are 64-bit signed, so nulls and larger integers can't be converted
+fn to_toml(value: &Value) -> std::result::Result<toml::Value, String> {
+    let converted = match value {
+        Value::Null => return Err(String::from("TOML doesn't support null values")),
+        Value::Bool(bool) => toml::Value::Boolean(*bool),
+        Value::Number(number) => match (number.as_i64(), number.as_f64()) {
+            (Some(integer), _) => toml::Value::Integer(integer),
+            (None, Some(float)) if number.is_f64() => toml::Value::Float(float),
+            _ => return Err(format!("TOML doesn't support integers larger than {}, but found {number}", i64::MAX))
+        },
+        Value::String(text) => toml::Value::String(text.clone()),
+        Value::Array(items) => toml::Value::Array(items.iter().map(to_toml).collect::<std::result::Result<Vec<toml::Value>, String>>()?),
+        Value::Object(map) => toml::Value::Table(map.iter()
+            .map(|(key, value)| Ok((key.clone(), to_toml(value)?)))
+            .collect::<std::result::Result<toml::Table, String>>()?)
+    };
+    Ok(converted)
+}
@@ -0,0 +1,186 @@
+//! Converting arrays of JSON objects to CSV with dotted column names, and CSV back to JSON
+
+use miette::SourceSpan;
+use serde_json::{Map, Number, Value};
+use crate::json::{self, JSONParseError};
+use crate::{Error, Result};
+
+/// How arrays nested inside the rows are written
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ArrayMode {
+    /// One column per item, e.g. `tags.0` and `tags.1`
+    #[default]
+    Index,
+    /// Scalar items joined with `;` in a single column. Arrays of objects or arrays are written as JSON
+    Join,
+    /// The whole array as JSON text in a single column
+    Json
+}
+
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    /// Columns to write, in this order. All columns in order of appearance when empty
+    pub columns: Vec<String>,
+    /// Sort the columns alphabetically instead of keeping their order of appearance
+    pub sort_columns: bool,
+    pub arrays: ArrayMode
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions {delimiter: b',', columns: Vec::new(), sort_columns: false, arrays: ArrayMode::default()}
+    }
+}
+
+/// Checks that the delimiter is a single byte, as the CSV reader and writer require
+pub fn delimiter(delimiter: char) -> Result<u8> {
+    u8::try_from(delimiter).ok()
+        .filter(u8::is_ascii)
+        .ok_or_else(|| Error::InvalidInput(format!("The delimiter '{delimiter}' is not an ASCII character!")))
+}
+
+/// Writes one row per item of the array (or a single row for an object). Nested objects become dotted column names
+/// and missing values empty cells. Fails when two values of a row get the same column, e.g. `{"a.b": 1, "a": {"b": 2}}`.
+pub fn from_json(value: &Value, options: &CsvOptions) -> Result<String> {
+    let items = match value {
+        Value::Array(items) => items.as_slice(),
+        value => std::slice::from_ref(value)
+    };
+
+    let mut columns = Vec::<String>::new();
+    let mut rows = Vec::new();
+    for item in items {
+        let mut row = Vec::new();
+        match item {
+            Value::Object(_) => flatten(item, "", options.arrays, &mut row)?,
+            // Arrays of scalars get a single column
+            value => row.push((String::from("value"), cell(value)?))
+        }
+        for (index, (column, _)) in row.iter().enumerate() {
+            if row[..index].iter().any(|(name, _)| name == column) {
+                return Err(Error::InvalidInput(format!("More than one value has the column '{column}'!")));
+            }
+            if !columns.contains(column) {
+                columns.push(column.clone());
+            }
+        }
+        rows.push(row);
+    }
+
+    if !options.columns.is_empty() {
+        columns = options.columns.clone();
+    } else if options.sort_columns {
+        columns.sort();
+    }
+
+    let mut writer = ::csv::WriterBuilder::new().delimiter(options.delimiter).from_writer(Vec::new());
+    let failed = |err: ::csv::Error| Error::InvalidInput(format!("Failed to write CSV! {err}"));
+    writer.write_record(&columns).map_err(failed)?;
+    for row in rows {
+        let record = columns.iter()
+            .map(|column| row.iter().find(|(name, _)| name == column).map_or("", |(_, value)| value.as_str()));
+        writer.write_record(record).map_err(failed)?;
+    }
+
+    let bytes = writer.into_inner().map_err(|err| Error::InvalidInput(format!("Failed to write CSV! {err}")))?;
+    Ok(String::from_utf8_lossy(&bytes).trim_end().to_string())
+}
+
+/// Reads the rows as objects keyed by the header. With `infer_types`, numbers, `true`/`false` and empty cells or `null`
+/// become JSON numbers, booleans and nulls. Fails when two columns have the same header. `src_name` is the name of the
+/// source shown in diagnostics.
+pub fn to_json(src: &str, delimiter: u8, infer_types: bool, src_name: &str) -> Result<Value> {
+    let syntax_error = |err: ::csv::Error| -> Error {
+        let offset = err.position().map_or(0, |position| position.byte() as usize).min(src.len());
+        JSONParseError::new(src_name, src.to_string(), SourceSpan::new(offset.into(), 0), format!("Failed to parse CSV: {err}")).into()
+    };
+
+    let mut reader = ::csv::ReaderBuilder::new().delimiter(delimiter).from_reader(src.as_bytes());
+    let headers = reader.headers().map_err(syntax_error)?.clone();
+    // The later column would overwrite the earlier one in every row
+    if let Some((index, header)) = headers.iter().enumerate().find(|(index, header)| headers.iter().take(*index).any(|other| other == *header)) {
+        return Err(Error::InvalidInput(format!("The header has more than one column named '{header}' (column {})!", index + 1)));
+    }
+    let rows = reader.records()
+        .map(|record| {
+            let record = record.map_err(syntax_error)?;
+            let row = headers.iter()
+                .zip(record.iter())
+                .map(|(header, field)| (header.to_string(), if infer_types {infer(field)} else {Value::String(field.to_string())}))
+                .collect::<Map<String, Value>>();
+            Ok(Value::Object(row))
+        })
+        .collect::<Result<Vec<Value>>>()?;
+    Ok(Value::Array(rows))
+}
+
+fn flatten(value: &Value, prefix: &str, arrays: ArrayMode, row: &mut Vec<(String, String)>) -> Result<()> {
+    let join = |key: &str| if prefix.is_empty() {key.to_string()} else {format!("{prefix}.{key}")};
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                flatten(value, &join(key), arrays, row)?;
+            }
+        },
+        Value::Array(items) if !items.is_empty() && arrays == ArrayMode::Index => {
+            for (index, item) in items.iter().enumerate() {
+                flatten(item, &join(&index.to_string()), arrays, row)?;
+            }
+        },
+        Value::Array(items) if arrays == ArrayMode::Join && items.iter().all(|item| !item.is_object() && !item.is_array()) => {
+            let cells = items.iter().map(cell).collect::<Result<Vec<String>>>()?;
+            row.push((prefix.to_string(), cells.join(";")));
+        },
+        value => row.push((prefix.to_string(), cell(value)?))
+    }
+    Ok(())
+}
+
+/// Strings are written without quotes, `null` as an empty cell and empty objects and arrays as JSON
+fn cell(value: &Value) -> Result<String> {
+    match value {
+        Value::Null => Ok(String::new()),
+        Value::String(text) => Ok(text.clone()),
+        value => json::minify(value)
+    }
+}
+
+fn infer(field: &str) -> Value {
+    match field {
+        "" | "null" => Value::Null,
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        // Only JSON number syntax, so values like `007` or `+1` stay strings. Numbers that would lose digits, like long
+        // IDs, stay strings too
+        field => serde_json::from_str::<Number>(field).ok()
+            .filter(|number| number.is_i64() || number.is_u64() || number.to_string() == field)
+            .map_or_else(|| Value::String(field.to_string()), Value::Number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+    use pretty_assertions::{assert_eq};
+
+    #[test]
+    fn keep_numbers_that_lose_precision_as_strings() -> Result<()> {
+        let json = to_json("id,card,ratio,big\n18446744073709551615,41111111111111111111111,0.5,1e3\n", b',', true, "content")?;
+        assert_eq!(json, json!([{"id": 18446744073709551615u64, "card": "41111111111111111111111", "ratio": 0.5, "big": "1e3"}]));
+        Ok(())
+    }
+
+    #[test]
+    fn reject_colliding_columns() {
+        let err = from_json(&json!([{"a.b": 1, "a": {"b": 2}}]), &CsvOptions::default()).expect_err("Both values have the column 'a.b'");
+        assert!(err.to_string().contains("'a.b'"), "Unexpected error: {err}");
+    }
+
+    #[test]
+    fn reject_duplicate_headers() {
+        let err = to_json("name,age,name\nann,30,bob\n", b',', false, "content").expect_err("Both columns are named 'name'");
+        assert!(err.to_string().contains("more than one column named 'name' (column 3)"), "Unexpected error: {err}");
+    }
+}
@@ -0,0 +1,58 @@
+//! Encoding and decoding of Base64, URL and hexadecimal representations
+
+use base64::alphabet::{STANDARD, URL_SAFE};
+use base64::Engine;
+use base64::engine::general_purpose::{NO_PAD, PAD};
+use base64::engine::GeneralPurpose;
+use crate::{Error, Result};
+
+/// Base64 variant used for encoding and decoding
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Base64Config {
+    /// Use the URL safe alphabet (`-` and `_` instead of `+` and `/`)
+    pub url_safe: bool,
+    /// Omit the `=` padding bytes at the end
+    pub no_padding: bool
+}
+
+impl Base64Config {
+    fn engine(&self) -> GeneralPurpose {
+        let alphabet = if self.url_safe {URL_SAFE} else {STANDARD};
+        let padding = if self.no_padding {NO_PAD} else {PAD};
+        GeneralPurpose::new(&alphabet, padding)
+    }
+}
+
+/// Encodes the bytes to Base64
+pub fn base64_encode(data: &[u8], config: Base64Config) -> String {
+    config.engine().encode(data)
+}
+
+/// Decodes a Base64 string
+pub fn base64_decode(text: &str, config: Base64Config) -> Result<Vec<u8>> {
+    config.engine().decode(text)
+        .map_err(|err| Error::InvalidInput(format!("Failed to decode BASE64 string! {err}")))
+}
+
+/// Percent-encodes the bytes so they can be used in URLs
+pub fn url_encode(data: &[u8]) -> String {
+    urlencoding::encode_binary(data).to_string()
+}
+
+/// Decodes a percent-encoded string
+pub fn url_decode(text: &str) -> Result<String> {
+    urlencoding::decode(text)
+        .map(|result| result.to_string())
+        .map_err(|err| Error::InvalidInput(format!("Failed to decode url string! {err}")))
+}
+
+/// Encodes the bytes as lowercase hexadecimal
+pub fn hex_encode(data: &[u8]) -> String {
+    hex::encode(data)
+}
+
+/// Decodes a hexadecimal string
+pub fn hex_decode(text: &str) -> Result<Vec<u8>> {
+    hex::decode(text)
+        .map_err(|err| Error::InvalidInput(format!("Failed to decode hex string! {err}")))
+}
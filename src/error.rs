@@ -0,0 +1,50 @@
+use std::fmt::{Display, Formatter};
+use std::{io, result};
+use crate::json::JSONParseError;
+use crate::json::query::QuerySyntaxError;
+
+/// Error returned by the `dx` library functions
+#[derive(Debug)]
+pub enum Error {
+    /// The provided input couldn't be parsed or processed
+    InvalidInput(String),
+    /// The provided JSON couldn't be parsed. Contains the source for rendering miette diagnostics
+    Json(Box<JSONParseError>),
+    /// The provided jq query couldn't be compiled. Contains the query for rendering miette diagnostics
+    Query(Box<QuerySyntaxError>),
+    /// Reading or writing a file failed
+    Io(io::Error)
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::InvalidInput(message) => write!(f, "{message}"),
+            Error::Json(err) => write!(f, "{err}"),
+            Error::Query(err) => write!(f, "{err}"),
+            Error::Io(err) => write!(f, "{err}")
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<JSONParseError> for Error {
+    fn from(value: JSONParseError) -> Self {
+        Error::Json(Box::new(value))
+    }
+}
+
+impl From<QuerySyntaxError> for Error {
+    fn from(value: QuerySyntaxError) -> Self {
+        Error::Query(Box::new(value))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Error::Io(value)
+    }
+}
+
+pub type Result<T> = result::Result<T, Error>;
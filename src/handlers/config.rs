@@ -0,0 +1,141 @@
+use std::{fs, result};
+use std::path::PathBuf;
+use serde::Serialize;
+use toml::{Table, Value};
+use toml_edit::DocumentMut;
+use crate::cli::{ConfigGetOptions, ConfigMethod, ConfigSetOptions};
+use crate::config::{self, Config, KEYS, PROJECT_FILE};
+use crate::handlers::{CommandHandlerError, CommandOutput, Result};
+
+#[derive(Serialize)]
+struct ConfigShowResult<'a> {
+    files: Vec<String>,
+    values: &'a Table
+}
+
+pub struct ConfigHandler {}
+
+impl ConfigHandler {
+    pub fn handle_method(method: &ConfigMethod) -> Result {
+        match method {
+            ConfigMethod::Show => Self::show_config(config::get()),
+            ConfigMethod::Get {options} => Self::get_value(config::get(), options),
+            ConfigMethod::Set {options} => Self::set_value(options)
+        }
+    }
+
+    fn show_config(config: &Config) -> Result {
+        let files = config.files.iter().map(|path| path.display().to_string()).collect::<Vec<String>>();
+        let values = toml::to_string(&config.values)
+            .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to serialize the configuration! {err}"))))?;
+
+        let text = if files.is_empty() {
+            let user_path = config::user_path().map(|path| path.display().to_string()).unwrap_or_default();
+            format!("# No config file found. Create '{user_path}' or a project-local '{PROJECT_FILE}'")
+        } else {
+            let sources = files.iter().map(|file| format!("# {file}")).collect::<Vec<String>>().join("\n");
+            format!("{sources}\n\n{}", values.trim_end())
+        };
+        Ok(CommandOutput::new(&ConfigShowResult {files, values: &config.values}, text))
+    }
+
+    fn get_value(config: &Config, options: &ConfigGetOptions) -> Result {
+        Self::check_key(&options.key)?;
+        let value = config.get(&options.key)
+            .ok_or_else(|| CommandHandlerError::NegativeResult(format!("'{}' is not set", options.key)))?;
+
+        let text = match value {
+            Value::String(text) => text.clone(),
+            value => value.to_string()
+        };
+        Ok(CommandOutput::new(value, text))
+    }
+
+    fn set_value(options: &ConfigSetOptions) -> Result {
+        Self::check_key(&options.key)?;
+        let path = if options.local {
+            PathBuf::from(PROJECT_FILE)
+        } else {
+            config::user_path()
+                .ok_or_else(|| CommandHandlerError::RuntimeError(Some(String::from("Failed to find the user config directory!"))))?
+        };
+
+        // Values that fail validation are replaced below, but a file that isn't TOML can't be edited without losing its content.
+        let mut document = if path.is_file() {
+            config::parse_document(&path).map_err(|err| CommandHandlerError::InvalidArguments(format!("{err}\nFix the syntax or remove the file to set values in it.")))?
+        } else {
+            DocumentMut::new()
+        };
+        let content = Self::set_in_document(&mut document, &options.key, &options.value)
+            .map_err(|section| CommandHandlerError::InvalidArguments(format!("'{section}' in '{}' is not a table!", path.display())))?;
+        Config::from_table(content.parse::<Table>()
+            .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to serialize the configuration! {err}"))))?)?;
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)
+                .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to create '{}'! {err}", dir.display()))))?;
+        }
+        fs::write(&path, content)
+            .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to write '{}'! {err}", path.display()))))?;
+
+        Ok(format!("Set '{}' to '{}' in '{}'", options.key, options.value, path.display()).into())
+    }
+
+    /// Sets the value, keeping the comment after a replaced one, and returns the new content of the file.
+    /// Fails with the name of the section when it isn't a table.
+    fn set_in_document(document: &mut DocumentMut, key: &str, value: &str) -> result::Result<String, String> {
+        let (section, name) = key.split_once('.').unwrap_or_default();
+        let table = document.entry(section)
+            .or_insert(toml_edit::table())
+            .as_table_like_mut()
+            .ok_or_else(|| section.to_string())?;
+        let mut value = Self::parse_value(value);
+        match table.get_mut(name).and_then(toml_edit::Item::as_value_mut) {
+            Some(old) => {
+                *value.decor_mut() = old.decor().clone();
+                *old = value;
+            },
+            None => {
+                table.insert(name, toml_edit::value(value));
+            }
+        }
+        Ok(document.to_string())
+    }
+
+    fn check_key(key: &str) -> result::Result<(), CommandHandlerError> {
+        if !KEYS.contains(&key) {
+            return Err(CommandHandlerError::InvalidArguments(format!("Unknown config key '{key}'! Available keys: {}", KEYS.join(", "))));
+        }
+        Ok(())
+    }
+
+    /// Booleans are stored as TOML booleans, everything else as strings
+    fn parse_value(value: &str) -> toml_edit::Value {
+        match value {
+            "true" => toml_edit::Value::from(true),
+            "false" => toml_edit::Value::from(false),
+            value => toml_edit::Value::from(value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use crate::utils::TestResult;
+    use super::*;
+
+    #[test]
+    fn set_keeps_comments_and_order() -> TestResult {
+        let mut document = "# My defaults\n[hash]\nalgorithm = \"md5\"  # fast\n\n[time]\ntimezone = \"UTC\"\n"
+            .parse::<DocumentMut>()
+            .map_err(|err| err.to_string())?;
+
+        ConfigHandler::set_in_document(&mut document, "hash.algorithm", "sha512")?;
+        let content = ConfigHandler::set_in_document(&mut document, "base64.url_safe", "true")?;
+        assert_eq!(content, "# My defaults\n[hash]\nalgorithm = \"sha512\"  # fast\n\n[time]\ntimezone = \"UTC\"\n\n[base64]\nurl_safe = true\n");
+
+        let mut document = "time = 1\n".parse::<DocumentMut>().map_err(|err| err.to_string())?;
+        assert_eq!(ConfigHandler::set_in_document(&mut document, "time.timezone", "UTC"), Err(String::from("time")));
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use dx::convert::{self, DataFormat};
+use crate::cli::ConvertOptions;
+use crate::handlers::{Result, CommandHandlerError, CommandOutput};
+use crate::input::{self, InputSource};
+
+pub struct ConvertHandler {}
+
+impl ConvertHandler {
+    pub fn handle_method(options: &ConvertOptions, stdin: &mut InputSource) -> Result {
+        let from = match (options.from, &options.file) {
+            (Some(from), _) => DataFormat::from(from),
+            (None, Some(file)) => DataFormat::from_path(file)
+                .ok_or_else(|| CommandHandlerError::InvalidArguments(format!("Can't detect the format of '{file}'! Use --from to set it")))?,
+            (None, None) => return Err(CommandHandlerError::MissingArguments(vec![String::from("from")]))
+        };
+
+        let (src_name, src) = input::read_file_or_content(options.file.as_ref(), options.content.as_ref(), stdin)?;
+
+        let value = convert::parse(&src, from, &src_name)?;
+        let text = convert::to_string(&value, DataFormat::from(options.to))?;
+        Ok(CommandOutput {data: value, text: text.trim_end().to_string()})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use crate::cli::convert::ConvertFormat;
+    use crate::input::Input;
+    use crate::utils::TestResult;
+    use super::*;
+
+    #[test]
+    fn convert_yaml_to_toml() -> TestResult {
+        let result = ConvertHandler::handle_method(&ConvertOptions {
+            file: None,
+            content: Some(Input::from("features:\n  - json\n  - yaml\nname: dx\nversion: 1.2\ndeps:\n  serde: {version: '1'}\n")),
+            from: Some(ConvertFormat::Yaml),
+            to: ConvertFormat::Toml
+        }, &mut InputSource::from_bytes(""))?;
+
+        assert_eq!(result.text, "features = [\"json\", \"yaml\"]\nname = \"dx\"\nversion = 1.2\n\n[deps.serde]\nversion = \"1\"");
+        Ok(())
+    }
+
+    #[test]
+    fn reject_toml_floats_json_lacks() {
+        let result = ConvertHandler::handle_method(&ConvertOptions {
+            file: None,
+            content: Some(Input::from("ratio = nan\nlimit = inf\n")),
+            from: Some(ConvertFormat::Toml),
+            to: ConvertFormat::Json
+        }, &mut InputSource::from_bytes(""));
+
+        let err = result.expect_err("JSON has no nan");
+        assert!(err.to_string().contains("JSON doesn't support NaN numbers, found at 'ratio'"), "Unexpected error: {err}");
+    }
+
+    #[test]
+    fn reject_yaml_floats_json_lacks() {
+        let convert = |content: &str| ConvertHandler::handle_method(&ConvertOptions {
+            file: None,
+            content: Some(Input::from(content)),
+            from: Some(ConvertFormat::Yaml),
+            to: ConvertFormat::Json
+        }, &mut InputSource::from_bytes(""));
+
+        for (content, expected) in [
+            ("a: .nan\n", "JSON doesn't support NaN numbers, found at 'a'"),
+            ("limits:\n  - 1\n  - .inf\n", "JSON doesn't support inf numbers, found at 'limits[1]'"),
+            ("b: {c: -.inf}\n", "JSON doesn't support -inf numbers, found at 'b.c'")
+        ] {
+            let err = convert(content).expect_err("JSON has no nan and inf");
+            assert!(err.to_string().contains(expected), "Unexpected error: {err}");
+        }
+    }
+
+    #[test]
+    fn reject_integers_toml_lacks() {
+        let result = ConvertHandler::handle_method(&ConvertOptions {
+            file: None,
+            content: Some(Input::from(r#"{"id": 18446744073709551615}"#)),
+            from: Some(ConvertFormat::Json),
+            to: ConvertFormat::Toml
+        }, &mut InputSource::from_bytes(""));
+
+        let err = result.expect_err("TOML integers are 64-bit signed");
+        assert!(err.to_string().contains("found 18446744073709551615"), "Unexpected error: {err}");
+    }
+}
@@ -0,0 +1,45 @@
+use dx::csv;
+use crate::cli::{CsvMethod, CsvToJsonOptions};
+use crate::handlers::{Result, CommandOutput};
+use crate::input::{self, InputSource};
+
+pub struct CsvHandler {}
+
+impl CsvHandler {
+    pub fn handle_method(method: &CsvMethod, stdin: &mut InputSource) -> Result {
+        match method {
+            CsvMethod::ToJson {options} => Self::to_json(options, stdin)
+        }
+    }
+
+    fn to_json(options: &CsvToJsonOptions, stdin: &mut InputSource) -> Result {
+        let (src_name, src) = input::read_file_or_content(options.file.as_ref(), options.content.as_ref(), stdin)?;
+        let json = csv::to_json(&src, csv::delimiter(options.delimiter)?, !options.no_infer, &src_name)?;
+        let text = dx::json::to_pretty(&json)?;
+        Ok(CommandOutput {data: json, text})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use crate::input::Input;
+    use crate::utils::TestResult;
+    use super::*;
+
+    #[test]
+    fn infer_cell_types() -> TestResult {
+        let result = CsvHandler::to_json(&CsvToJsonOptions {
+            file: None,
+            content: Some(Input::from("id;name;active;score;zip\n1;Ann;true;9.5;007\n2;\"Bo; Jr\";false;;null\n")),
+            delimiter: ';',
+            no_infer: false
+        }, &mut InputSource::from_bytes(""))?;
+
+        assert_eq!(result.data, serde_json::json!([
+            {"id": 1, "name": "Ann", "active": true, "score": 9.5, "zip": "007"},
+            {"id": 2, "name": "Bo; Jr", "active": false, "score": null, "zip": null}
+        ]));
+        Ok(())
+    }
+}
@@ -1,42 +1,30 @@
-use base64::alphabet::{STANDARD, URL_SAFE};
-use base64::Engine;
-use base64::engine::general_purpose::{NO_PAD, PAD};
-use base64::engine::GeneralPurpose;
+use dx::encode;
 use crate::cli::{Base64Options, DecodeMethod, HexOptions, URLOptions};
-use crate::handlers::{Result, CommandHandlerError};
+use crate::handlers::{Result};
+use crate::input::InputSource;
 
 pub struct DecodeHandler {}
 
 impl DecodeHandler {
-    pub fn handle_method(method: &DecodeMethod) -> Result {
+    pub fn handle_method(method: &DecodeMethod, stdin: &mut InputSource) -> Result {
         match method {
-            DecodeMethod::Base64 {options} => Self::decode_base64(options),
-            DecodeMethod::URL {options} => Self::decode_url(options),
-            DecodeMethod::Hex {options} => Self::decode_hex(options),
+            DecodeMethod::Base64 {options} => Self::decode_base64(options, stdin),
+            DecodeMethod::URL {options} => Self::decode_url(options, stdin),
+            DecodeMethod::Hex {options} => Self::decode_hex(options, stdin),
         }
     }
 
-    fn decode_base64(options: &Base64Options) -> Result {
-        let alphabet = if options.url_safe {URL_SAFE} else {STANDARD};
-        let padding = if options.no_padding {NO_PAD} else {PAD};
-        let engine = GeneralPurpose::new(&alphabet, padding);
-        match engine.decode(&options.text) {
-            Ok(result) => Ok(String::from_utf8_lossy(&result).to_string()),
-            Err(err) => Err(CommandHandlerError::RuntimeError(Some(format!("Failed to decode BASE64 string! {err}"))))
-        }
+    fn decode_base64(options: &Base64Options, stdin: &mut InputSource) -> Result {
+        let result = encode::base64_decode(&options.text.read(stdin)?, options.into())?;
+        Ok(String::from_utf8_lossy(&result).to_string().into())
     }
 
-    fn decode_url(options: &URLOptions) -> Result {
-        match urlencoding::decode(&options.text) {
-            Ok(result) => Ok(result.to_string()),
-            Err(err) => Err(CommandHandlerError::RuntimeError(Some(format!("Failed to decode url string! {err}"))))
-        }
+    fn decode_url(options: &URLOptions, stdin: &mut InputSource) -> Result {
+        Ok(encode::url_decode(&options.text.read(stdin)?)?.into())
     }
 
-    fn decode_hex(options: &HexOptions) -> Result {
-        match hex::decode(&options.text) {
-            Ok(result) => Ok(String::from_utf8_lossy(&result).to_string()),
-            Err(err) => Err(CommandHandlerError::RuntimeError(Some(format!("Failed to decode hex string! {err}"))))
-        }
+    fn decode_hex(options: &HexOptions, stdin: &mut InputSource) -> Result {
+        let result = encode::hex_decode(&options.text.read(stdin)?)?;
+        Ok(String::from_utf8_lossy(&result).to_string().into())
     }
-}
\ No newline at end of file
+}
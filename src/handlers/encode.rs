@@ -1,34 +1,28 @@
-use base64::alphabet::{STANDARD, URL_SAFE};
-use base64::Engine;
-use base64::engine::general_purpose::{NO_PAD, PAD};
-use base64::engine::GeneralPurpose;
+use dx::encode;
 use crate::cli::{Base64Options, EncodeMethod, HexOptions, URLOptions};
 use crate::handlers::{Result};
+use crate::input::InputSource;
 
 pub struct EncodeHandler {}
 
 impl EncodeHandler {
-    pub fn handle_method(method: &EncodeMethod) -> Result {
+    pub fn handle_method(method: &EncodeMethod, stdin: &mut InputSource) -> Result {
         match method {
-            EncodeMethod::Base64 {options} => Self::encode_base64(options),
-            EncodeMethod::URL {options} => Self::encode_url(options),
-            EncodeMethod::Hex {options} => Self::encode_hex(options),
+            EncodeMethod::Base64 {options} => Self::encode_base64(options, stdin),
+            EncodeMethod::URL {options} => Self::encode_url(options, stdin),
+            EncodeMethod::Hex {options} => Self::encode_hex(options, stdin),
         }
     }
 
-    fn encode_base64(options: &Base64Options) -> Result {
-        let alphabet = if options.url_safe {URL_SAFE} else {STANDARD};
-        let padding = if options.no_padding {NO_PAD} else {PAD};
-        Ok(
-            GeneralPurpose::new(&alphabet, padding).encode(&options.text)
-        )
+    fn encode_base64(options: &Base64Options, stdin: &mut InputSource) -> Result {
+        Ok(encode::base64_encode(&options.text.read_bytes(stdin)?, options.into()).into())
     }
 
-    fn encode_url(options: &URLOptions) -> Result {
-        Ok(urlencoding::encode(&options.text).to_string())
+    fn encode_url(options: &URLOptions, stdin: &mut InputSource) -> Result {
+        Ok(encode::url_encode(&options.text.read_bytes(stdin)?).into())
     }
 
-    fn encode_hex(options: &HexOptions) -> Result {
-        Ok(hex::encode(&options.text))
+    fn encode_hex(options: &HexOptions, stdin: &mut InputSource) -> Result {
+        Ok(encode::hex_encode(&options.text.read_bytes(stdin)?).into())
     }
-}
\ No newline at end of file
+}
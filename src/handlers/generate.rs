@@ -0,0 +1,55 @@
+use std::fs;
+use clap::CommandFactory;
+use crate::cli::{Cli, CompletionsOptions, ManOptions};
+use crate::handlers::{CommandHandlerError, Result};
+
+pub struct CompletionsHandler {}
+
+impl CompletionsHandler {
+    pub fn handle_method(options: &CompletionsOptions) -> Result {
+        let mut script = Vec::new();
+        clap_complete::generate(options.shell, &mut Cli::command(), "dx", &mut script);
+
+        let script = String::from_utf8(script)
+            .map_err(|_| CommandHandlerError::RuntimeError(Some(String::from("The generated completion script is not valid UTF-8!"))))?;
+        Ok(script.into())
+    }
+}
+
+pub struct ManHandler {}
+
+impl ManHandler {
+    pub fn handle_method(options: &ManOptions) -> Result {
+        if let Some(dir) = &options.dir {
+            fs::create_dir_all(dir)
+                .and_then(|_| clap_mangen::generate_to(Cli::command(), dir))
+                .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to write the man pages to '{dir}'! {err}"))))?;
+            return Ok(format!("Man pages written to '{dir}'").into());
+        }
+
+        let mut page = Vec::new();
+        clap_mangen::Man::new(Cli::command()).render(&mut page)
+            .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to render the man page! {err}"))))?;
+
+        let page = String::from_utf8(page)
+            .map_err(|_| CommandHandlerError::RuntimeError(Some(String::from("The generated man page is not valid UTF-8!"))))?;
+        Ok(page.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap_complete::Shell;
+    use super::*;
+
+    #[test]
+    fn complete_enumerated_values() {
+        let result = CompletionsHandler::handle_method(&CompletionsOptions {shell: Shell::Bash});
+
+        assert!(result.is_ok());
+        let script = result.unwrap().text;
+        for value in ["sha512", "HS384", "Europe/Sofia", "from-unix"] {
+            assert!(script.contains(value), "Completion script is missing '{value}'");
+        }
+    }
+}
@@ -1,53 +1,60 @@
 use std::fs;
-use md5::{Md5, Digest as Md5Digest};
-use sha2::{Sha256, Sha512, Digest as Sha256Digest};
-use crate::cli::{HashAlgorithm, HashFileOptions, HashMethod, HashVerifyOptions};
-use crate::handlers::{Result, CommandHandlerError};
+use dx::hash::{self, HashAlgorithm};
+use serde::Serialize;
+use crate::cli::{HashFileOptions, HashMethod, HashVerifyOptions};
+use crate::handlers::{Result, CommandHandlerError, CommandOutput};
+use crate::input::InputSource;
+
+#[derive(Serialize)]
+struct HashResult<'a> {
+    algorithm: HashAlgorithm,
+    hash: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<&'a str>
+}
+
+#[derive(Serialize)]
+struct HashVerifyResult<'a> {
+    algorithm: HashAlgorithm,
+    expected: &'a str,
+    matches: bool
+}
 
 pub struct HashHandler {}
 
 impl HashHandler {
-    pub fn handle_method(method: &HashMethod) -> Result {
+    pub fn handle_method(method: &HashMethod, stdin: &mut InputSource) -> Result {
         match method {
-            HashMethod::Md5 {options} => Ok(Self::hash_md5(&options.text)),
-            HashMethod::Sha256 {options} => Ok(Self::hash_sha256(&options.text)),
-            HashMethod::Sha512 {options} => Ok(Self::hash_sha512(&options.text)),
+            HashMethod::Md5 {options} => Ok(Self::hash_output(HashAlgorithm::Md5, &options.text.read_bytes(stdin)?, None)),
+            HashMethod::Sha256 {options} => Ok(Self::hash_output(HashAlgorithm::Sha256, &options.text.read_bytes(stdin)?, None)),
+            HashMethod::Sha512 {options} => Ok(Self::hash_output(HashAlgorithm::Sha512, &options.text.read_bytes(stdin)?, None)),
             HashMethod::File {options} => Self::hash_file(options),
-            HashMethod::Verify {options} => Self::verify_hash(options)
+            HashMethod::Verify {options} => Self::verify_hash(options, stdin)
         }
     }
 
-    fn hash_md5(text: &str) -> String { hex::encode(Md5::digest(text.as_bytes())) }
-    fn hash_sha256(text: &str) -> String { hex::encode(Sha256::digest(text.as_bytes())) }
-    fn hash_sha512(text: &str) -> String { hex::encode(Sha512::digest(text.as_bytes())) }
+    pub fn hash_output(algorithm: HashAlgorithm, data: &[u8], path: Option<&str>) -> CommandOutput {
+        let hash = hash::digest(algorithm, data);
+        CommandOutput::new(&HashResult {algorithm, hash: hash.clone(), path}, hash)
+    }
 
     fn hash_file(options: &HashFileOptions) -> Result {
-        let file_content = fs::read_to_string(&options.path)
+        let file_content = fs::read(&options.path)
             .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to read file contents! {err}"))))?;
 
-        return Ok(
-            match options.algorithm {
-                HashAlgorithm::Md5 => Self::hash_md5(&file_content),
-                HashAlgorithm::Sha256 => Self::hash_sha256(&file_content),
-                HashAlgorithm::Sha512 => Self::hash_sha512(&file_content),
-            }
-        );
+        Ok(Self::hash_output((&options.algorithm).into(), &file_content, Some(&options.path)))
     }
 
-    fn verify_hash(options: &HashVerifyOptions) -> Result {
-        let new_hash = {
-            match options.algorithm {
-                HashAlgorithm::Md5 => Self::hash_md5(&options.text),
-                HashAlgorithm::Sha256 => Self::hash_sha256(&options.text),
-                HashAlgorithm::Sha512 => Self::hash_sha512(&options.text),
-            }
-        };
-
-        if new_hash == options.expected {
-            return Ok(String::from("The provided text matches the expected hash!"))
+    fn verify_hash(options: &HashVerifyOptions, stdin: &mut InputSource) -> Result {
+        let algorithm = HashAlgorithm::from(&options.algorithm);
+        if hash::verify(algorithm, &options.text.read_bytes(stdin)?, &options.expected) {
+            return Ok(CommandOutput::new(
+                &HashVerifyResult {algorithm, expected: &options.expected, matches: true},
+                String::from("The provided text matches the expected hash!")
+            ))
         }
 
-        return Err(CommandHandlerError::NegativeResult("The provided string doesn't match the expected one!".to_string()));
+        Err(CommandHandlerError::NegativeResult("The provided string doesn't match the expected one!".to_string()))
     }
 }
 
@@ -55,15 +62,27 @@ impl HashHandler {
 mod tests {
     use crate::cli::{HashAlgorithm, HashVerifyOptions};
     use crate::handlers::hash::HashHandler;
+    use crate::input::{Input, InputSource};
 
     #[test]
     fn verify_hash() {
         let is_valid = HashHandler::verify_hash(&HashVerifyOptions{
-            text: "Hello, World".to_string(),
+            text: Input::from("Hello, World"),
             expected: "03675ac53ff9cd1535ccc7dfcdfa2c458c5218371f418dc136f2d19ac1fbe8a5".to_string(),
             algorithm: HashAlgorithm::Sha256,
-        }).is_ok();
+        }, &mut InputSource::from_bytes("")).is_ok();
 
         assert!(is_valid, "The provided text should match the expected hash!");
     }
+
+    #[test]
+    fn verify_hash_from_stdin() {
+        let is_valid = HashHandler::verify_hash(&HashVerifyOptions{
+            text: Input::Stdin,
+            expected: "03675ac53ff9cd1535ccc7dfcdfa2c458c5218371f418dc136f2d19ac1fbe8a5".to_string(),
+            algorithm: HashAlgorithm::Sha256,
+        }, &mut InputSource::from_bytes("Hello, World")).is_ok();
+
+        assert!(is_valid, "The text from stdin should match the expected hash!");
+    }
 }
\ No newline at end of file
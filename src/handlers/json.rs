@@ -1,96 +1,852 @@
 use std::{fs, result};
-use std::error::Error;
-use std::fmt::{Display, Formatter};
-use miette::{NamedSource, SourceSpan, Diagnostic};
-use crate::cli::{FormatOptions, JSONMethod, MinifyOptions, ValidateOptions};
-use crate::handlers::{Result, CommandHandlerError};
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use colored::Colorize;
+use dx::csv::{self, CsvOptions};
+use dx::json::{self, Indent, Style};
+use dx::json::{canonical, diff, flatten, infer, patch, path, pointer, schema, stream, types};
+use dx::json::diff::ChangeKind;
+use dx::json::flatten::PathStyle;
+use dx::json::patch::Outcome;
+use dx::json::schema::{SchemaDraft, SchemaValidationError};
+use dx::json::query::Query;
+use dx::json::stream::Layout;
+use serde_json::Value;
+use crate::cli::{CanonicalizeOptions, DeleteOptions, DiffOptions, FlattenOptions, FormatOptions, GetOptions, JSONHashOptions, JSONMethod, MinifyOptions, PatchOptions, QueryOptions, SchemaInferOptions, SchemaMethod, SetOptions, StyleOptions, ToCsvOptions, TypesOptions, UnflattenOptions, ValidateOptions};
+use crate::handlers::{Result, CommandHandlerError, CommandOutput};
+use crate::handlers::hash::HashHandler;
+use crate::input::{self, Input, InputSource};
+use crate::stream::{Progress, Summary};
 
-#[derive(Debug, Diagnostic)]
-struct JSONParseError {
-    #[source_code]
-    src: NamedSource<String>,
+/// Read buffer of streamed input
+const STREAM_BUFFER: usize = 64 * 1024;
 
-    #[label("error occurred here")]
-    err_span: SourceSpan,
-
-    message: String
+#[derive(Default)]
+pub struct JSONHandler {
+    /// Accept JSON5 (comments, trailing commas, single quotes, unquoted keys) instead of strict JSON
+    lenient: bool
 }
 
-impl Display for JSONParseError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.message)
+impl JSONHandler {
+    pub fn handle_method(method: &JSONMethod, lenient: bool, stdin: &mut InputSource) -> Result {
+        let handler = JSONHandler {lenient};
+        match method {
+            JSONMethod::Format {options} => handler.format(options, stdin),
+            JSONMethod::Minify {options} => handler.minify(options, stdin),
+            JSONMethod::Validate {options} => handler.validate(options, stdin),
+            JSONMethod::Query {options} => handler.query(options, stdin),
+            JSONMethod::Get {options} => handler.get(options, stdin),
+            JSONMethod::Set {options} => handler.set(options, stdin),
+            JSONMethod::Delete {options} => handler.delete(options, stdin),
+            JSONMethod::Diff {options} => handler.diff(options, stdin),
+            JSONMethod::Patch {options} => handler.patch(options, stdin),
+            JSONMethod::ToCsv {options} => handler.to_csv(options, stdin),
+            JSONMethod::Canonicalize {options} => handler.canonicalize(options, stdin),
+            JSONMethod::Hash {options} => handler.hash(options, stdin),
+            JSONMethod::Types {options} => handler.types(options, stdin),
+            JSONMethod::Flatten {options} => handler.flatten(options, stdin),
+            JSONMethod::Unflatten {options} => handler.unflatten(options, stdin),
+            JSONMethod::Schema {method: SchemaMethod::Infer {options}} => handler.infer_schema(options, stdin)
+        }
     }
-}
 
-impl Error for JSONParseError {}
+    /// Reads NDJSON, or the items of a top-level array, one value at a time from the file or stdin and writes the
+    /// results as they are produced, so memory use doesn't grow with the input
+    pub fn stream(method: &JSONMethod, stdin: &mut InputSource, writer: &mut dyn Write, show_progress: bool) -> result::Result<Summary, CommandHandlerError> {
+        let not_streamable = || CommandHandlerError::InvalidArguments(String::from("Only format, minify, validate and query can be streamed!"));
+        let (file, layout) = match method {
+            JSONMethod::Format {options} => (options.file.as_ref(), &options.layout),
+            JSONMethod::Minify {options} => (options.file.as_ref(), &options.layout),
+            JSONMethod::Validate {options} => (options.file.as_ref(), &options.layout),
+            JSONMethod::Query {options} => (options.file.as_ref(), &options.layout),
+            _ => return Err(not_streamable())
+        };
+        let (input, total): (Box<dyn Read + '_>, Option<u64>) = match file {
+            Some(file) if file != "-" => {
+                let input = File::open(file)
+                    .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to read JSON file! {}", err))))?;
+                let total = input.metadata().ok().map(|metadata| metadata.len());
+                (Box::new(input), total)
+            },
+            _ => (Box::new(stdin.reader()?), None)
+        };
 
-pub struct JSONHandler {}
+        let mut progress = Progress::new(total, show_progress);
+        let mut reader = BufReader::with_capacity(STREAM_BUFFER, progress.track(input));
+        let layout = match layout.layout() {
+            Some(layout) => layout,
+            None => stream::layout(&mut reader)?
+        };
+        let result = match method {
+            JSONMethod::Format {options} => Self::stream_format(&options.style, reader, layout, writer, &mut progress),
+            JSONMethod::Minify {..} => Self::stream_minify(reader, layout, writer, &mut progress),
+            JSONMethod::Validate {options} => Self::schema_validator(options)
+                .and_then(|validator| Self::stream_validate(validator.as_ref(), reader, layout, writer, &mut progress)),
+            JSONMethod::Query {options} => Self::stream_query(options, reader, layout, writer, &mut progress),
+            _ => Err(not_streamable())
+        };
+        progress.finish();
+        result
+    }
 
-impl JSONHandler {
-    pub fn handle_method(method: &JSONMethod) -> Result {
-        match method {
-            JSONMethod::Format {options} => Self::format(options),
-            JSONMethod::Minify {options} => Self::minify(options),
-            JSONMethod::Validate {options} => Self::validate(options)
-        }
-    }
-
-    fn extract_json(file: Option<&String>, content: Option<&String>) -> result::Result<serde_json::Value, CommandHandlerError> {
-        let mut src_name = String::new();
-        let json_str = {
-            if file.is_some() {
-                src_name.push_str(file.unwrap());
-                fs::read_to_string(file.unwrap())
-                    .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to read JSON file! {}", err))))?
-            } else if content.is_some() {
-                src_name.push_str("Provided JSON content");
-                content.unwrap().to_string()
-            } else {
-                return Err(CommandHandlerError::MissingArgumentsSome(vec!["file".to_string(), "content".to_string()]))
+    /// A top-level array stays an array with every item indented one level, other values are formatted one after another
+    fn stream_format(style_options: &StyleOptions, reader: impl Read, layout: Layout, writer: &mut dyn Write, progress: &mut Progress) -> result::Result<Summary, CommandHandlerError> {
+        let style = Style::from(style_options);
+        let indent = match style.indent {
+            Indent::Spaces(spaces) => " ".repeat(spaces),
+            Indent::Tabs => String::from("\t")
+        };
+        let mut written = 0;
+        if layout == Layout::Array {
+            write_all(writer, "[")?;
+        }
+        let records = stream::for_each(reader, layout, |mut value| {
+            if style_options.sort_keys {
+                json::sort_keys(&mut value);
             }
+            let text = json::format(&value, &style)?;
+            let text = match layout {
+                Layout::Array => {
+                    let separator = if written == 0 {"\n"} else {",\n"};
+                    let lines = text.lines().map(|line| format!("{indent}{line}")).collect::<Vec<String>>();
+                    format!("{separator}{}", lines.join("\n"))
+                },
+                Layout::Values => format!("{text}\n")
+            };
+            written += 1;
+            progress.record();
+            write_all(writer, &text)
+        })?;
+        if layout == Layout::Array {
+            write_all(writer, if records == 0 {"]\n"} else {"\n]\n"})?;
+        }
+        Ok(Summary {records, invalid: 0})
+    }
+
+    /// A top-level array stays an array on a single line, other values are written one per line (NDJSON)
+    fn stream_minify(reader: impl Read, layout: Layout, writer: &mut dyn Write, progress: &mut Progress) -> result::Result<Summary, CommandHandlerError> {
+        if layout == Layout::Array {
+            write_all(writer, "[")?;
+        }
+        let records = stream::for_each(reader, layout, |value| {
+            let minified = json::minify(&value)?;
+            let text = match layout {
+                Layout::Array if progress.records() > 0 => format!(",{minified}"),
+                Layout::Array => minified,
+                Layout::Values => format!("{minified}\n")
+            };
+            progress.record();
+            write_all(writer, &text)
+        })?;
+        if layout == Layout::Array {
+            write_all(writer, "]\n")?;
+        }
+        Ok(Summary {records, invalid: 0})
+    }
+
+    /// The compiled `--schema`, if given. Streaming rejects `--lenient`, so the schema is strict JSON.
+    fn schema_validator(options: &ValidateOptions) -> result::Result<Option<schema::Validator>, CommandHandlerError> {
+        let Some(schema) = JSONHandler::default().read_schema(options)? else {
+            return Ok(None);
         };
+        Ok(Some(schema::Validator::new(&schema, options.draft.as_ref().map(SchemaDraft::from))?))
+    }
 
-        return Ok(
-            serde_json::from_str(&json_str)
-                .map_err(|err| {
-                    let line = err.line();
-                    let col = err.column();
+    /// The `--schema` document, if given, parsed like the input
+    fn read_schema(&self, options: &ValidateOptions) -> result::Result<Option<Value>, CommandHandlerError> {
+        let Some(schema_path) = &options.schema else {
+            return Ok(None);
+        };
+        let schema_str = fs::read_to_string(schema_path)
+            .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to read JSON Schema file! {}", err))))?;
+        self.parse(&schema_str, schema_path).map(Some)
+    }
+
+    /// Every value that doesn't match the schema is reported with its record number. A syntax error stops the stream.
+    fn stream_validate(validator: Option<&schema::Validator>, reader: impl Read, layout: Layout, writer: &mut dyn Write, progress: &mut Progress) -> result::Result<Summary, CommandHandlerError> {
+        let mut invalid = 0;
+        let records = stream::for_each(reader, layout, |value| {
+            progress.record();
+            let Some(validator) = validator else {
+                return Ok(());
+            };
+            let violations = validator.validate(&value);
+            if violations.is_empty() {
+                return Ok(());
+            }
+            invalid += 1;
+            let lines = violations.iter()
+                .map(|violation| format!("record {}: {}: {}\n", progress.records(), violation.instance_path, violation.message))
+                .collect::<String>();
+            write_all(writer, &lines)
+        })?;
+        if invalid == 0 {
+            write_all(writer, "The provided JSON is valid!\n")?;
+        }
+        Ok(Summary {records, invalid})
+    }
+
+    /// The query runs on every value and its results are written as they come
+    fn stream_query(options: &QueryOptions, reader: impl Read, layout: Layout, writer: &mut dyn Write, progress: &mut Progress) -> result::Result<Summary, CommandHandlerError> {
+        let query = Query::compile(&options.query)?;
+        let records = stream::for_each(reader, layout, |value| {
+            let results = query.run(&value)?;
+            progress.record();
+            if results.is_empty() {
+                return Ok(());
+            }
+            let text = Self::values_text(results.iter(), options.raw_output, options.compact)
+                .map_err(|err| dx::Error::InvalidInput(err.to_string()))?;
+            write_all(writer, &format!("{text}\n"))
+        })?;
+        Ok(Summary {records, invalid: 0})
+    }
+
+    fn extract_json(&self, file: Option<&String>, content: Option<&Input>, stdin: &mut InputSource) -> result::Result<serde_json::Value, CommandHandlerError> {
+        let (src_name, json_str) = input::read_file_or_content(file, content, stdin)?;
+        self.parse(&json_str, &src_name)
+    }
 
-                    let offset = json_str.lines().take(line).map(|l| l.len() + 1).sum::<usize>() + col - 1;
+    fn parse(&self, json_str: &str, src_name: &str) -> result::Result<Value, CommandHandlerError> {
+        Ok(self.parse_json(json_str, src_name)?)
+    }
+
+    /// Strict JSON, or JSON5 with `--lenient`. Errors that JSON5 would accept point to `--lenient`.
+    fn parse_json(&self, json_str: &str, src_name: &str) -> dx::Result<Value> {
+        if self.lenient {
+            return json::parse_lenient(json_str, src_name);
+        }
+        json::parse(json_str, src_name).map_err(|err| match err {
+            dx::Error::Json(mut err) if err.is_json5() => {
+                err.add_help("Use --lenient to accept them");
+                dx::Error::Json(err)
+            },
+            err => err
+        })
+    }
+
+    fn format(&self, options: &FormatOptions, stdin: &mut InputSource) -> Result {
+        let json = self.extract_json(options.file.as_ref(), options.content.as_ref(), stdin)?;
+        let output = Self::formatted(json, &options.style)?;
+
+        match &options.file {
+            Some(file) if options.write && file != "-" => {
+                let newline = if options.no_trailing_newline {""} else {"\n"};
+                fs::write(file, format!("{}{newline}", output.text))
+                    .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to write '{file}'! {err}"))))?;
+                Ok(CommandOutput {data: output.data, text: format!("Formatted '{file}'")})
+            },
+            Some(_) if options.write => Err(CommandHandlerError::InvalidArguments(String::from("Only files can be formatted in place!"))),
+            _ => Ok(output)
+        }
+    }
+
+    fn minify(&self, options: &MinifyOptions, stdin: &mut InputSource) -> Result {
+        let json = self.extract_json(options.file.as_ref(), options.content.as_ref(), stdin)?;
+        let minified_json = json::minify(&json)?;
+        Ok(CommandOutput {data: json, text: minified_json})
+    }
 
-                    let parse_error = JSONParseError {
-                        src: NamedSource::new(src_name, json_str),
-                        err_span: SourceSpan::new(offset.into(), 1),
-                        message: format!("Failed to parse JSON: {err}")
+    fn validate(&self, options: &ValidateOptions, stdin: &mut InputSource) -> Result {
+        let (src_name, json_str) = input::read_file_or_content(options.file.as_ref(), options.content.as_ref(), stdin)?;
+        // Invalid JSON is a negative result like a schema violation, not a runtime error
+        let json = match self.parse_json(&json_str, &src_name) {
+            Ok(json) => json,
+            Err(dx::Error::Json(err)) => {
+                let data = serde_json::json!({"valid": false, "error": err.to_string()});
+                return Err(CommandHandlerError::NegativeOutput(CommandOutput {data, text: format!("{:?}", miette::Report::new(*err))}));
+            },
+            Err(err) => return Err(err.into())
+        };
+
+        if let Some(schema) = self.read_schema(options)? {
+            return Self::check_schema(&schema, options.draft.as_ref().map(SchemaDraft::from), &json, &src_name, json_str);
+        }
+
+        Ok(CommandOutput::new(&serde_json::json!({"valid": true}), "The provided JSON is valid!".to_string()))
+    }
+
+    /// The violations are returned as data for `--output-format json` and underlined in the source otherwise
+    fn check_schema(schema: &Value, draft: Option<SchemaDraft>, json: &Value, src_name: &str, json_str: String) -> Result {
+        let violations = schema::validate(schema, json, draft)?;
+        if violations.is_empty() {
+            return Ok(CommandOutput::new(&serde_json::json!({"valid": true}), "The provided JSON is valid!".to_string()));
+        }
+
+        let report = miette::Report::new(SchemaValidationError::new(src_name, json_str, &violations));
+        let data = serde_json::json!({"valid": false, "violations": violations});
+        Err(CommandHandlerError::NegativeOutput(CommandOutput {data, text: format!("{report:?}")}))
+    }
+
+    fn query(&self, options: &QueryOptions, stdin: &mut InputSource) -> Result {
+        // Compile first so a broken query is reported before stdin is read
+        let query = Query::compile(&options.query)?;
+        let json = self.extract_json(options.file.as_ref(), options.content.as_ref(), stdin)?;
+        let results = query.run(&json)?;
+
+        let text = Self::values_text(results.iter(), options.raw_output, options.compact)?;
+        Ok(CommandOutput {data: Value::Array(results), text})
+    }
+
+    fn get(&self, options: &GetOptions, stdin: &mut InputSource) -> Result {
+        let json = self.extract_json(options.file.as_ref(), options.content.as_ref(), stdin)?;
+        let not_found = || CommandHandlerError::NegativeResult(format!("Nothing found at '{}'", options.path));
+
+        // JSONPath expressions always start with the root identifier, JSON Pointers never do
+        if options.path.starts_with('$') {
+            let matches = path::select(&json, &options.path)?;
+            if matches.is_empty() {
+                return Err(not_found());
+            }
+            let text = Self::values_text(matches.iter().map(|found| found.value), options.raw_output, false)?;
+            return Ok(CommandOutput::new(&matches, text));
+        }
+
+        let value = pointer::get(&json, &options.path)?.ok_or_else(not_found)?;
+        let text = Self::values_text([value].into_iter(), options.raw_output, false)?;
+        Ok(CommandOutput::new(value, text))
+    }
+
+    fn set(&self, options: &SetOptions, stdin: &mut InputSource) -> Result {
+        let mut json = self.extract_json(options.file.as_ref(), options.content.as_ref(), stdin)?;
+        let value = match serde_json::from_str::<Value>(&options.value) {
+            Ok(value) if !options.string => value,
+            _ => Value::String(options.value.clone())
+        };
+
+        pointer::set(&mut json, &options.pointer, value)?;
+        Self::formatted(json, &options.style)
+    }
+
+    fn delete(&self, options: &DeleteOptions, stdin: &mut InputSource) -> Result {
+        let mut json = self.extract_json(options.file.as_ref(), options.content.as_ref(), stdin)?;
+        pointer::delete(&mut json, &options.pointer)?;
+        Self::formatted(json, &options.style)
+    }
+
+    fn diff(&self, options: &DiffOptions, stdin: &mut InputSource) -> Result {
+        if options.old == "-" && options.new == "-" {
+            return Err(CommandHandlerError::InvalidArguments(String::from("Only one of the documents can be read from stdin!")));
+        }
+        let old = self.extract_json(Some(&options.old), None, stdin)?;
+        let new = self.extract_json(Some(&options.new), None, stdin)?;
+
+        let diff_options = diff::DiffOptions {ignore_array_order: options.ignore_order, ignore_keys: options.ignore_key.clone()};
+        let changes = diff::diff(&old, &new, &diff_options);
+        if options.patch {
+            return Self::formatted(diff::to_patch(&changes, &diff_options), &StyleOptions::default());
+        }
+        if changes.is_empty() {
+            return Ok(CommandOutput::new(&changes, String::from("No differences")));
+        }
+
+        let compact = |value: &Option<Value>| json::minify(value.as_ref().unwrap_or(&Value::Null));
+        let lines = changes.iter()
+            .map(|change| {
+                let line = match change.kind {
+                    ChangeKind::Added => format!("+ {}: {}", change.pointer, compact(&change.new)?).green(),
+                    ChangeKind::Removed => format!("- {}: {}", change.pointer, compact(&change.old)?).red(),
+                    ChangeKind::Changed => format!("~ {}: {} -> {}", change.pointer, compact(&change.old)?, compact(&change.new)?).yellow()
+                };
+                Ok(line.to_string())
+            })
+            .collect::<dx::Result<Vec<String>>>()?;
+        Ok(CommandOutput::new(&changes, lines.join("\n")))
+    }
+
+    fn patch(&self, options: &PatchOptions, stdin: &mut InputSource) -> Result {
+        if options.document == "-" && options.patch == "-" {
+            return Err(CommandHandlerError::InvalidArguments(String::from("Only one of the document and the patch can be read from stdin!")));
+        }
+        let mut document = self.extract_json(Some(&options.document), None, stdin)?;
+        let patch = self.extract_json(Some(&options.patch), None, stdin)?;
+
+        if options.merge {
+            patch::merge(&mut document, &patch);
+        } else {
+            document = match patch::apply(&document, &patch::parse(&patch)?)? {
+                Outcome::Patched(patched) => patched,
+                Outcome::TestFailed {index, path, expected, actual} => {
+                    let actual = match actual {
+                        Some(actual) => json::minify(&actual)?,
+                        None => String::from("nothing")
                     };
+                    return Err(CommandHandlerError::NegativeResult(
+                        format!("Test operation {index} failed! Expected {} at '{path}' but found {actual}", json::minify(&expected)?)
+                    ));
+                }
+            };
+        }
+
+        let output = Self::formatted(document, &options.style)?;
+        if options.dry_run || options.document == "-" {
+            return Ok(output);
+        }
+        fs::write(&options.document, format!("{}\n", output.text))
+            .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to write '{}'! {err}", options.document))))?;
+        Ok(CommandOutput {data: output.data, text: format!("Patched '{}'", options.document)})
+    }
 
-                    CommandHandlerError::RuntimeError(Some(
-                        format!("{:?}", miette::Report::new(parse_error))
-                    ))
-                })?
-        );
+    fn to_csv(&self, options: &ToCsvOptions, stdin: &mut InputSource) -> Result {
+        let json = self.extract_json(options.file.as_ref(), options.content.as_ref(), stdin)?;
+        let text = csv::from_json(&json, &CsvOptions {
+            delimiter: csv::delimiter(options.delimiter)?,
+            columns: options.columns.clone(),
+            sort_columns: options.sort_columns,
+            arrays: options.arrays.into()
+        })?;
+        Ok(CommandOutput::new(&text, text.clone()))
     }
 
-    fn format(options: &FormatOptions) -> Result {
-        let json = Self::extract_json(options.file.as_ref(), options.content.as_ref())?;
+    fn canonicalize(&self, options: &CanonicalizeOptions, stdin: &mut InputSource) -> Result {
+        let json = self.extract_json(options.file.as_ref(), options.content.as_ref(), stdin)?;
+        let canonical = canonical::canonicalize(&json)?;
+        Ok(CommandOutput::new(&canonical, canonical.clone()))
+    }
 
-        let pretty_json = serde_json::to_string_pretty(&json)
-            .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to prettify JSON! {}", err))))?;
+    /// Hashes the canonical form, so documents differing only in whitespace, key order or number notation get the same digest
+    fn hash(&self, options: &JSONHashOptions, stdin: &mut InputSource) -> Result {
+        let json = self.extract_json(options.file.as_ref(), options.content.as_ref(), stdin)?;
+        let canonical = canonical::canonicalize(&json)?;
+        Ok(HashHandler::hash_output((&options.algorithm).into(), canonical.as_bytes(), options.file.as_deref()))
+    }
 
-        return Ok(pretty_json);
+    /// Infers one schema from all the samples, so fields missing from some of them become optional
+    fn types(&self, options: &TypesOptions, stdin: &mut InputSource) -> Result {
+        let documents = self.extract_samples(&options.files, stdin)?;
+        let samples = documents.iter().flat_map(|document| match document {
+            Value::Array(items) => items.iter().collect(),
+            document => vec![document]
+        });
+        let code = types::generate(&infer::infer_all(samples), &options.name, options.lang.into())?;
+        Ok(CommandOutput::new(&code, code.clone()))
     }
 
-    fn minify(options: &MinifyOptions) -> Result {
-        let json = Self::extract_json(options.file.as_ref(), options.content.as_ref())?;
+    /// Every sample is a whole document, so a top-level array infers an array schema
+    fn infer_schema(&self, options: &SchemaInferOptions, stdin: &mut InputSource) -> Result {
+        let documents = self.extract_samples(&options.files, stdin)?;
+        Self::formatted(schema::infer(&documents), &options.style)
+    }
 
-        let minified_json = serde_json::to_string(&json)
-            .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to minify JSON! {}", err))))?;
+    fn flatten(&self, options: &FlattenOptions, stdin: &mut InputSource) -> Result {
+        let json = self.extract_json(options.file.as_ref(), options.content.as_ref(), stdin)?;
+        let flat = flatten::flatten(&json, &PathStyle::from(&options.paths))?;
+        if !options.lines {
+            return Self::formatted(Value::Object(flat), &options.style);
+        }
 
-        return Ok(minified_json);
+        let lines = flat.iter()
+            .map(|(path, value)| match value {
+                Value::String(text) => Ok(format!("{path}={text}")),
+                value => Ok(format!("{path}={}", json::minify(value)?))
+            })
+            .collect::<dx::Result<Vec<String>>>()?;
+        Ok(CommandOutput {data: Value::Object(flat), text: lines.join("\n")})
     }
 
-    fn validate(options: &ValidateOptions) -> Result {
-        Self::extract_json(options.file.as_ref(), options.content.as_ref())?;
-        return Ok("The provided JSON is valid!".to_string());
+    fn unflatten(&self, options: &UnflattenOptions, stdin: &mut InputSource) -> Result {
+        let json = self.extract_json(options.file.as_ref(), options.content.as_ref(), stdin)?;
+        let nested = flatten::unflatten(&json, &PathStyle::from(&options.paths))?;
+        Self::formatted(nested, &options.style)
     }
-}
\ No newline at end of file
+
+    /// The sample documents from the files, or from stdin when there are none
+    fn extract_samples(&self, files: &[String], stdin: &mut InputSource) -> result::Result<Vec<Value>, CommandHandlerError> {
+        if files.is_empty() {
+            return Ok(vec![self.extract_json(None, None, stdin)?]);
+        }
+        files.iter().map(|file| self.extract_json(Some(file), None, stdin)).collect()
+    }
+
+    /// The whole document rendered the same way as `json format`. Sorting keys also sorts the `--output-format json` data
+    fn formatted(mut json: Value, style: &StyleOptions) -> Result {
+        if style.sort_keys {
+            json::sort_keys(&mut json);
+        }
+        let text = json::format(&json, &Style::from(style))?;
+        Ok(CommandOutput {data: json, text})
+    }
+
+    /// One value per line, with strings optionally unquoted
+    fn values_text<'a>(values: impl Iterator<Item = &'a Value>, raw_output: bool, compact: bool) -> result::Result<String, CommandHandlerError> {
+        let lines = values
+            .map(|value| match value {
+                Value::String(text) if raw_output => Ok(text.clone()),
+                value if compact => json::minify(value),
+                value => json::to_pretty(value)
+            })
+            .collect::<dx::Result<Vec<String>>>()?;
+        Ok(lines.join("\n"))
+    }
+}
+
+fn write_all(writer: &mut dyn Write, text: &str) -> dx::Result<()> {
+    Ok(writer.write_all(text.as_bytes())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use crate::cli::HashAlgorithm;
+    use crate::cli::json::{PathArrayNotation, PathOptions, StreamLayoutOptions, TypesLanguage};
+    use crate::utils::{TempFile, TestResult};
+    use super::*;
+
+    #[test]
+    fn query_supported_subset() {
+        let result = JSONHandler::default().query(&QueryOptions {
+            query: String::from(".items | map(select(.price > 10)) | .[] | {name, tags: (.tags[1:] | length)}"),
+            file: None,
+            content: Some(Input::from(r#"{"items": [{"name": "a", "price": 5, "tags": []}, {"name": "b", "price": 20, "tags": ["x", "y", "z"]}]}"#)),
+            raw_output: false,
+            compact: true,
+            stream: false,
+            layout: StreamLayoutOptions::default()
+        }, &mut InputSource::from_bytes(""));
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().text, r#"{"name":"b","tags":2}"#);
+    }
+
+    #[test]
+    fn report_schema_violations_as_data() {
+        let schema = serde_json::json!({"properties": {"id": {"type": "integer"}}, "required": ["name"]});
+        let json_str = String::from(r#"{"id": "1"}"#);
+        let json = serde_json::from_str::<Value>(&json_str).expect("The JSON is valid");
+
+        let Err(CommandHandlerError::NegativeOutput(output)) = JSONHandler::check_schema(&schema, None, &json, "content", json_str) else {
+            panic!("The JSON doesn't match the schema!");
+        };
+        assert_eq!(output.data, serde_json::json!({"valid": false, "violations": [
+            {"instance_path": "", "schema_path": "/required", "message": "\"name\" is a required property"},
+            {"instance_path": "/id", "schema_path": "/properties/id/type", "message": "\"1\" is not of type \"integer\""}
+        ]}));
+        assert!(output.text.contains("Found 2 violation(s)"), "Unexpected report: {}", output.text);
+    }
+
+    #[test]
+    fn report_invalid_json_as_negative_result() {
+        let result = JSONHandler::default().validate(&ValidateOptions {
+            file: None,
+            content: Some(Input::from(r#"{"id": 1,}"#)),
+            schema: None,
+            draft: None,
+            stream: false,
+            layout: StreamLayoutOptions::default()
+        }, &mut InputSource::from_bytes(""));
+
+        let Err(CommandHandlerError::NegativeOutput(output)) = result else {
+            panic!("Invalid JSON is a negative result!");
+        };
+        assert_eq!(output.data["valid"], Value::Bool(false));
+        assert!(output.text.contains("Failed to parse JSON"), "Unexpected report: {}", output.text);
+    }
+
+    #[test]
+    fn edit_at_pointer() -> TestResult {
+        let content = Some(Input::from(r#"{"name": "dx", "a~b": [1, 2], "scripts": {"test": "cargo test"}}"#));
+        let result = JSONHandler::default().set(&SetOptions {
+            pointer: String::from("/a~0b/-"),
+            value: String::from("3"),
+            string: false,
+            file: None,
+            content: content.clone(),
+            style: StyleOptions::default()
+        }, &mut InputSource::from_bytes(""))?;
+        assert_eq!(result.data["a~b"], serde_json::json!([1, 2, 3]));
+
+        let result = JSONHandler::default().delete(&DeleteOptions {
+            pointer: String::from("/scripts/test"),
+            file: None,
+            content,
+            style: StyleOptions::default()
+        }, &mut InputSource::from_bytes(""))?;
+        assert_eq!(result.data["scripts"], serde_json::json!({}));
+        Ok(())
+    }
+
+    #[test]
+    fn patch_exit_codes() -> TestResult {
+        let document = TempFile::new(r#"{"name": "dx", "version": 1}"#)?;
+        let patch = |patch: &str| JSONHandler::default().patch(&PatchOptions {
+            document: document.path(),
+            patch: String::from("-"),
+            merge: false,
+            dry_run: true,
+            style: StyleOptions::default()
+        }, &mut InputSource::from_bytes(patch));
+
+        let err = patch(r#"[{"op": "test", "path": "/name", "value": "jq"}]"#).expect_err("The test operation fails");
+        assert_eq!(err.exit_code(), 1);
+        let err = patch(r#"[{"op": "move", "from": "/version", "path": "/meta/version"}]"#).expect_err("/meta doesn't exist");
+        assert_eq!(err.exit_code(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn patch_dry_run_leaves_the_document() -> TestResult {
+        let content = r#"{"name": "dx", "version": 1}"#;
+        let document = TempFile::new(content)?;
+        let patch = |dry_run| JSONHandler::default().patch(&PatchOptions {
+            document: document.path(),
+            patch: String::from("-"),
+            merge: false,
+            dry_run,
+            style: StyleOptions::default()
+        }, &mut InputSource::from_bytes(r#"[{"op": "replace", "path": "/version", "value": 2}]"#));
+
+        assert_eq!(patch(true)?.data, serde_json::json!({"name": "dx", "version": 2}));
+        assert_eq!(fs::read_to_string(&document.path).map_err(|err| err.to_string())?, content);
+
+        patch(false)?;
+        assert_eq!(fs::read_to_string(&document.path).map_err(|err| err.to_string())?, "{\n  \"name\": \"dx\",\n  \"version\": 2\n}\n");
+        Ok(())
+    }
+
+    #[test]
+    fn format_with_default_style() -> TestResult {
+        let result = JSONHandler::default().format(&FormatOptions {
+            file: None,
+            content: Some(Input::from(r#"{"name": "dx", "tags": ["cli", "json"]}"#)),
+            style: StyleOptions::default(),
+            write: false,
+            no_trailing_newline: false,
+            stream: false,
+            layout: StreamLayoutOptions::default()
+        }, &mut InputSource::from_bytes(""))?;
+
+        assert_eq!(result.text, "{\n  \"name\": \"dx\",\n  \"tags\": [\n    \"cli\",\n    \"json\"\n  ]\n}");
+        Ok(())
+    }
+
+    #[test]
+    fn format_with_tabs_sorted_keys_and_compact_arrays() -> TestResult {
+        let result = JSONHandler::default().format(&FormatOptions {
+            file: None,
+            content: Some(Input::from(r#"{"name": "dx", "tags": ["cli", "json"], "deps": [{"b": 2, "a": 1}]}"#)),
+            style: StyleOptions {indent: 2, tabs: true, sort_keys: true, compact_arrays: true},
+            write: false,
+            no_trailing_newline: false,
+            stream: false,
+            layout: StreamLayoutOptions::default()
+        }, &mut InputSource::from_bytes(""))?;
+
+        assert_eq!(result.text, "{\n\t\"deps\": [\n\t\t{\n\t\t\t\"a\": 1,\n\t\t\t\"b\": 2\n\t\t}\n\t],\n\t\"name\": \"dx\",\n\t\"tags\": [\"cli\", \"json\"]\n}");
+        Ok(())
+    }
+
+    #[test]
+    fn canonicalize_numbers_strings_and_keys() -> TestResult {
+        let result = JSONHandler::default().canonicalize(&CanonicalizeOptions {
+            file: None,
+            content: Some(Input::from(r#"{"b": [1.0E2, 1e-7, 1e21, -0, "\u20ac\n"], "a": {"\u00e9": true, "z": null}}"#))
+        }, &mut InputSource::from_bytes(""))?;
+
+        assert_eq!(result.text, "{\"a\":{\"z\":null,\"\u{e9}\":true},\"b\":[100,1e-7,1e+21,0,\"\u{20ac}\\n\"]}");
+        Ok(())
+    }
+
+    #[test]
+    fn hash_canonical_form() -> TestResult {
+        let formatted = JSONHandler::default().hash(&JSONHashOptions {
+            file: None,
+            content: Some(Input::from("{\"a\": 1, \"b\": 2.50}")),
+            algorithm: HashAlgorithm::Sha256
+        }, &mut InputSource::from_bytes(""))?;
+        let canonical = JSONHandler::default().hash(&JSONHashOptions {
+            file: None,
+            content: Some(Input::from("{\"b\":2.5,\"a\":1}")),
+            algorithm: HashAlgorithm::Sha256
+        }, &mut InputSource::from_bytes(""))?;
+
+        assert_eq!(formatted.text, canonical.text);
+        Ok(())
+    }
+
+    #[test]
+    fn accept_json5_when_lenient() -> TestResult {
+        let result = JSONHandler {lenient: true}.minify(&MinifyOptions {
+            file: None,
+            content: Some(Input::from("{\n  // comment\n  compilerOptions: {'strict': true, /* trailing */},\n}")),
+            stream: false,
+            layout: StreamLayoutOptions::default()
+        }, &mut InputSource::from_bytes(""))?;
+
+        assert_eq!(result.text, r#"{"compilerOptions":{"strict":true}}"#);
+        Ok(())
+    }
+
+    #[test]
+    fn reject_json5_when_strict() {
+        let result = JSONHandler::default().minify(&MinifyOptions {
+            file: None,
+            content: Some(Input::from("{\n  // comment\n  \"strict\": true\n}")),
+            stream: false,
+            layout: StreamLayoutOptions::default()
+        }, &mut InputSource::from_bytes(""));
+
+        let err = result.expect_err("Comments aren't valid JSON");
+        assert_eq!(err.exit_code(), 3);
+        assert!(err.to_string().contains("Use --lenient to accept them"), "Unexpected error: {err}");
+    }
+
+    #[test]
+    fn report_every_syntax_error() {
+        let err = JSONHandler::default().minify(&MinifyOptions {
+            file: None,
+            content: Some(Input::from("{\r\n  \"näme\": \"é\" \"x\": 1,\r\n  key: [1, 2,],\r\n}")),
+            stream: false,
+            layout: StreamLayoutOptions::default()
+        }, &mut InputSource::from_bytes("")).expect_err("The JSON is invalid");
+
+        assert_eq!(err.exit_code(), 3);
+        assert!(err.to_string().contains("found 4 syntax errors"), "Unexpected error: {err}");
+    }
+
+    const USER_SAMPLES: &str = r#"[
+        {"id": 1, "userName": "ann", "role": "admin", "address": {"city": "Oslo"}, "tags": ["a"]},
+        {"id": 2, "userName": "bob", "role": "user", "address": {"city": "Rome", "zip": null}, "tags": []},
+        {"id": 3, "userName": "cy", "role": "admin", "address": {"city": "Riga"}, "tags": [], "score": 1.5}
+    ]"#;
+
+    #[test]
+    fn generate_typescript_from_samples() -> TestResult {
+        let result = JSONHandler::default().types(&TypesOptions {
+            files: Vec::new(),
+            lang: TypesLanguage::Typescript,
+            name: String::from("User")
+        }, &mut InputSource::from_bytes(USER_SAMPLES))?;
+
+        assert_eq!(result.text, [
+            "export interface User {\n  id: number;\n  userName: string;\n  role: Role;\n  address: Address;\n  tags: string[];\n  score?: number;\n}",
+            "export type Role = \"admin\" | \"user\";",
+            "export interface Address {\n  city: string;\n  zip?: unknown;\n}"
+        ].join("\n\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn generate_rust_from_samples() -> TestResult {
+        let result = JSONHandler::default().types(&TypesOptions {
+            files: Vec::new(),
+            lang: TypesLanguage::Rust,
+            name: String::from("User")
+        }, &mut InputSource::from_bytes(USER_SAMPLES))?;
+
+        assert!(result.text.contains("    #[serde(rename = \"userName\")]\n    pub user_name: String,"), "{}", result.text);
+        assert!(result.text.contains("    #[serde(rename = \"admin\")]\n    Admin,"), "{}", result.text);
+        Ok(())
+    }
+
+    fn stream(method: JSONMethod, input: &str) -> result::Result<(Summary, String), CommandHandlerError> {
+        let mut output = Vec::new();
+        let summary = JSONHandler::stream(&method, &mut InputSource::from_bytes(input), &mut output, false)?;
+        Ok((summary, String::from_utf8_lossy(&output).to_string()))
+    }
+
+    #[test]
+    fn stream_minify() -> TestResult {
+        let method = JSONMethod::Minify {options: MinifyOptions {file: None, content: None, stream: true, layout: StreamLayoutOptions::default()}};
+        let (summary, output) = stream(method, "{\"id\": 1, \"tags\": [\"a\"]}\n\n{\"id\": \"2\"}\n")?;
+        assert_eq!(summary, Summary {records: 2, invalid: 0});
+        assert_eq!(output, "{\"id\":1,\"tags\":[\"a\"]}\n{\"id\":\"2\"}\n");
+        Ok(())
+    }
+
+    #[test]
+    fn stream_ndjson_of_arrays() -> TestResult {
+        let method = JSONMethod::Minify {options: MinifyOptions {
+            file: None,
+            content: None,
+            stream: true,
+            layout: StreamLayoutOptions {ndjson: true, array: false}
+        }};
+        assert_eq!(stream(method, "[1, 2]\n[3]\n")?, (Summary {records: 2, invalid: 0}, String::from("[1,2]\n[3]\n")));
+        Ok(())
+    }
+
+    #[test]
+    fn stream_format_array() -> TestResult {
+        let method = JSONMethod::Format {options: FormatOptions {
+            file: None,
+            content: None,
+            style: StyleOptions {compact_arrays: true, ..StyleOptions::default()},
+            write: false,
+            no_trailing_newline: false,
+            stream: true,
+            layout: StreamLayoutOptions::default()
+        }};
+        let (summary, output) = stream(method, "[{\"id\": 1, \"tags\": [1, 2]}, 3]")?;
+        assert_eq!(summary.records, 2);
+        assert_eq!(output, "[\n  {\n    \"id\": 1,\n    \"tags\": [1, 2]\n  },\n  3\n]\n");
+        Ok(())
+    }
+
+    #[test]
+    fn stream_validate_against_schema() -> TestResult {
+        let validator = schema::Validator::new(&serde_json::json!({"properties": {"id": {"type": "integer"}}}), None).map_err(|err| err.to_string())?;
+        let mut output = Vec::new();
+        let summary = JSONHandler::stream_validate(Some(&validator), "{\"id\": 1}\n{\"id\": \"2\"}\n".as_bytes(), Layout::Values, &mut output, &mut Progress::new(None, false))?;
+        assert_eq!(summary, Summary {records: 2, invalid: 1});
+        assert_eq!(String::from_utf8_lossy(&output), "record 2: /id: \"2\" is not of type \"integer\"\n");
+        Ok(())
+    }
+
+    #[test]
+    fn stream_query() -> TestResult {
+        let method = JSONMethod::Query {options: QueryOptions {
+            query: String::from(".id"),
+            file: None,
+            content: None,
+            raw_output: false,
+            compact: true,
+            stream: true,
+            layout: StreamLayoutOptions::default()
+        }};
+        assert_eq!(stream(method, "{\"id\": 1}\n{\"id\": \"2\"}\n")?.1, "1\n\"2\"\n");
+        Ok(())
+    }
+
+    const CONFIG: &str = r#"{"db": {"host": "x", "ports": [1, 2], "opts": {}}, "users": [{"name": "a"}, {"name": null}]}"#;
+
+    #[test]
+    fn flatten_to_lines() -> TestResult {
+        let result = JSONHandler::default().flatten(&FlattenOptions {
+            file: None,
+            content: Some(Input::from(CONFIG)),
+            paths: PathOptions {separator: String::from("__"), arrays: PathArrayNotation::Index},
+            lines: true,
+            style: StyleOptions::default()
+        }, &mut InputSource::from_bytes(""))?;
+
+        assert_eq!(result.text, "db__host=x\ndb__ports__0=1\ndb__ports__1=2\ndb__opts={}\nusers__0__name=a\nusers__1__name=null");
+        Ok(())
+    }
+
+    #[test]
+    fn unflatten_flattened_document() -> TestResult {
+        let flat = r#"{"db__host": "x", "db__ports__0": 1, "db__ports__1": 2, "db__opts": {}, "users__0__name": "a", "users__1__name": null}"#;
+        let result = JSONHandler::default().unflatten(&UnflattenOptions {
+            file: None,
+            content: Some(Input::from(flat)),
+            paths: PathOptions {separator: String::from("__"), arrays: PathArrayNotation::Index},
+            style: StyleOptions::default()
+        }, &mut InputSource::from_bytes(""))?;
+
+        assert_eq!(result.data, serde_json::from_str::<Value>(CONFIG).map_err(|err| err.to_string())?);
+        Ok(())
+    }
+
+    #[test]
+    fn unflatten_bracket_paths() -> TestResult {
+        let result = JSONHandler::default().unflatten(&UnflattenOptions {
+            file: None,
+            content: Some(Input::from(r#"{"[1].a": 1, "[0]": true}"#)),
+            paths: PathOptions {separator: String::from("."), arrays: PathArrayNotation::Brackets},
+            style: StyleOptions::default()
+        }, &mut InputSource::from_bytes(""))?;
+
+        assert_eq!(result.data, serde_json::json!([true, {"a": 1}]));
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
-use rand::{Rng};
+use dx::lorem;
 use crate::cli::{LoremMethod, LoremOptions};
-use crate::handlers::{Result};
+use crate::handlers::{CommandOutput, Result};
 
 pub struct LoremHandler {}
 
@@ -14,33 +14,17 @@ impl LoremHandler {
     }
 
     fn gen_words(options: &LoremOptions) -> Result {
-        let mut rng = rand::rng();
-        return Ok(lipsum::lipsum_words_from_seed(options.count as usize, rng.random()));
+        Ok(lorem::words(options.count).into())
     }
 
     fn gen_sentences(options: &LoremOptions) -> Result {
-        let mut rng = rand::rng();
-        return Ok(
-            (1..=options.count).map(|_| {
-                let num_words = rng.random_range(5..12);
-                let seed = rng.random();
-                return lipsum::lipsum_words_from_seed(num_words, seed);
-            }).collect::<Vec<String>>().join("\u{001F}")
-        )
+        let sentences = lorem::sentences(options.count);
+        Ok(CommandOutput::new(&sentences, sentences.join("\u{001F}")))
     }
 
     fn gen_paragraphs(options: &LoremOptions) -> Result {
-        let mut rng = rand::rng();
-        return Ok(
-            (1..=options.count).map(|_| {
-                let num_sentences = rng.random_range(5..7);
-                return (1..=num_sentences).map(|_| {
-                    let num_words = rng.random_range(10..25);
-                    let seed = rng.random();
-                    return lipsum::lipsum_words_from_seed(num_words, seed);
-                }).collect::<Vec<String>>().join(" ");
-            }).collect::<Vec<String>>().join("\n------------\n\u{001F}")
-        )
+        let paragraphs = lorem::paragraphs(options.count);
+        Ok(CommandOutput::new(&paragraphs, paragraphs.join("\n------------\n\u{001F}")))
     }
 }
 
@@ -53,14 +37,14 @@ mod tests {
     fn generate_ten_lorem_words() -> TestResult {
         match LoremHandler::gen_words(&LoremOptions {count: 10}) {
             Ok(result) => {
-                let count = result.split(" ").map(|val| val.to_string()).collect::<Vec<String>>().len();
-                if (count != 10) {
+                let count = result.text.split(" ").map(|val| val.to_string()).collect::<Vec<String>>().len();
+                if count != 10 {
                     return Err(format!("The generated lorem has {count} words!"));
                 }
 
                 Ok(())
             },
-            Err(err) => Err(String::from("Failed to generate lorem words!"))
+            Err(_) => Err(String::from("Failed to generate lorem words!"))
         }
     }
 
@@ -68,15 +52,15 @@ mod tests {
     fn generate_ten_lorem_sentences() -> TestResult {
         match LoremHandler::gen_sentences(&LoremOptions {count: 10}) {
             Ok(result) => {
-                println!("{result}");
-                let count = result.split("\u{001F}").map(|val| val.to_string()).collect::<Vec<String>>().len();
-                if (count != 10) {
+                println!("{}", result.text);
+                let count = result.text.split("\u{001F}").map(|val| val.to_string()).collect::<Vec<String>>().len();
+                if count != 10 {
                     return Err(format!("The generated lorem has {count} sentences!"));
                 }
 
                 Ok(())
             },
-            Err(err) => Err(String::from("Failed to generate lorem sentences!"))
+            Err(_) => Err(String::from("Failed to generate lorem sentences!"))
         }
     }
 
@@ -84,14 +68,14 @@ mod tests {
     fn generate_ten_lorem_paragraphs() -> TestResult {
         match LoremHandler::gen_paragraphs(&LoremOptions {count: 10}) {
             Ok(result) => {
-                let count = result.split("\u{001F}").map(|val| val.to_string()).collect::<Vec<String>>().len();
-                if (count != 10) {
+                let count = result.text.split("\u{001F}").map(|val| val.to_string()).collect::<Vec<String>>().len();
+                if count != 10 {
                     return Err(format!("The generated lorem has {count} paragraphs!"));
                 }
 
                 Ok(())
             },
-            Err(err) => Err(String::from("Failed to generate lorem paragraphs!"))
+            Err(_) => Err(String::from("Failed to generate lorem paragraphs!"))
         }
     }
 }
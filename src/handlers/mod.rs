@@ -1,4 +1,6 @@
 mod json;
+mod convert;
+mod csv;
 mod encode;
 mod decode;
 mod uuid;
@@ -8,30 +10,45 @@ mod hash;
 mod jwt;
 mod regex;
 mod lorem;
+mod pipe;
+mod generate;
+mod config;
 
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::result;
+use serde::Serialize;
+use serde_json::Value;
 
-use crate::cli::{Command};
+use crate::cli::{Command, OutputFormat};
+use crate::handlers::config::ConfigHandler;
+use crate::handlers::convert::ConvertHandler;
+use crate::handlers::csv::CsvHandler;
 use crate::handlers::decode::DecodeHandler;
 use crate::handlers::encode::EncodeHandler;
+use crate::handlers::generate::{CompletionsHandler, ManHandler};
 use crate::handlers::hash::HashHandler;
-use crate::handlers::json::JSONHandler;
+pub use crate::handlers::json::JSONHandler;
 use crate::handlers::jwt::JWTHandler;
 use crate::handlers::lorem::LoremHandler;
+use crate::handlers::pipe::PipeHandler;
 use crate::handlers::regex::RegexHandler;
 use crate::handlers::text::TextHandler;
 use crate::handlers::time::TimeHandler;
 use crate::handlers::uuid::UuidHandler;
+use crate::input::InputSource;
+use crate::stream;
 use crate::utils::TestError;
 
 #[derive(Debug)]
 pub enum CommandHandlerError {
     RuntimeError(Option<String>),
     NegativeResult(String),
+    /// Negative result with structured data for `--output-format json`, e.g. the violations of a failed validation
+    NegativeOutput(CommandOutput),
     MissingArguments(Vec<String>),
     MissingArgumentsSome(Vec<String>),
+    InvalidArguments(String),
     UnknownError
 }
 
@@ -40,8 +57,10 @@ impl Display for CommandHandlerError {
         match self {
             CommandHandlerError::RuntimeError(message) => write!(f, "{}", message.as_ref().unwrap_or(&String::from("A runtime error occurred!"))),
             CommandHandlerError::NegativeResult(message) => write!(f, "{message}"),
+            CommandHandlerError::NegativeOutput(output) => write!(f, "{}", output.text),
             CommandHandlerError::MissingArguments(args) => write!(f, "Required arguments: {:?}", args),
             CommandHandlerError::MissingArgumentsSome(args) => write!(f, "At least one of the following arguments must be provided: {:?}", args),
+            CommandHandlerError::InvalidArguments(message) => write!(f, "{message}"),
             CommandHandlerError::UnknownError => write!(f, "Unknown error")
         }
     }
@@ -49,35 +68,116 @@ impl Display for CommandHandlerError {
 
 impl Error for CommandHandlerError {}
 
-impl Into<TestError> for CommandHandlerError {
-    fn into(self) -> TestError {
-        return format!("{self}")
+impl CommandHandlerError {
+    /// Process exit code reported for the error.
+    /// 1 - negative result, 2 - usage error or missing arguments, 3 - runtime or IO error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CommandHandlerError::NegativeResult(_) | CommandHandlerError::NegativeOutput(_) => 1,
+            CommandHandlerError::MissingArguments(_) | CommandHandlerError::MissingArgumentsSome(_) | CommandHandlerError::InvalidArguments(_) => 2,
+            CommandHandlerError::RuntimeError(_) | CommandHandlerError::UnknownError => 3
+        }
+    }
+}
+
+impl From<dx::Error> for CommandHandlerError {
+    fn from(value: dx::Error) -> Self {
+        match value {
+            // Render the diagnostic with the underlined source
+            dx::Error::Json(err) => CommandHandlerError::RuntimeError(Some(format!("{:?}", miette::Report::new(*err)))),
+            dx::Error::Query(err) => CommandHandlerError::InvalidArguments(format!("{:?}", miette::Report::new(*err))),
+            err => CommandHandlerError::RuntimeError(Some(err.to_string()))
+        }
     }
 }
 
-pub type Result = result::Result<String, CommandHandlerError>;
+impl From<CommandHandlerError> for TestError {
+    fn from(value: CommandHandlerError) -> Self {
+        format!("{value}")
+    }
+}
+
+/// Result of a command: structured data for `--output-format json` and its human readable rendering
+#[derive(Debug)]
+pub struct CommandOutput {
+    pub data: Value,
+    pub text: String
+}
+
+impl CommandOutput {
+    pub fn new<T: Serialize>(data: &T, text: String) -> Self {
+        CommandOutput {
+            data: serde_json::to_value(data).unwrap_or_default(),
+            text
+        }
+    }
+
+    pub fn render(&self, format: &OutputFormat) -> String {
+        match format {
+            OutputFormat::Text => self.text.clone(),
+            OutputFormat::Json => serde_json::to_string_pretty(&self.data).unwrap_or_default()
+        }
+    }
+}
+
+impl From<String> for CommandOutput {
+    fn from(text: String) -> Self {
+        CommandOutput {data: Value::String(text.clone()), text}
+    }
+}
+
+pub type Result = result::Result<CommandOutput, CommandHandlerError>;
 
 pub struct CommandHandler {
-    command: Command
+    command: Command,
+    stdin: InputSource
 }
 
 impl CommandHandler {
     pub fn new(command: Command) -> Self {
-        CommandHandler {command}
+        CommandHandler {command, stdin: InputSource::stdin()}
     }
 
-    pub fn handle(&self) -> Result {
-        return match &self.command {
-            Command::Json {method} => JSONHandler::handle_method(method),
-            Command::Encode {method} => EncodeHandler::handle_method(method),
-            Command::Decode {method} => DecodeHandler::handle_method(method),
-            Command::Hash {method} => HashHandler::handle_method(method),
+    /// Replaces the stream from which omitted or `-` inputs are read
+    pub fn with_stdin(mut self, stdin: InputSource) -> Self {
+        self.stdin = stdin;
+        self
+    }
+
+    pub fn handle(&mut self) -> Result {
+        let stdin = &mut self.stdin;
+        match &self.command {
+            // Streaming writes its results as they come, which only works for a whole run of dx
+            command if stream::requested(command) => Err(CommandHandlerError::InvalidArguments(String::from("--stream can only be used directly, not in pipelines, the shell or batch mode!"))),
+            Command::Json {lenient, method} => JSONHandler::handle_method(method, *lenient, stdin),
+            Command::Convert {options} => ConvertHandler::handle_method(options, stdin),
+            Command::Csv {method} => CsvHandler::handle_method(method, stdin),
+            Command::Encode {method} => EncodeHandler::handle_method(method, stdin),
+            Command::Decode {method} => DecodeHandler::handle_method(method, stdin),
+            Command::Hash {method} => HashHandler::handle_method(method, stdin),
             Command::Uuid {method} => UuidHandler::handle_method(method),
-            Command::Time {method} => TimeHandler::handle_method(method),
-            Command::Text {method} => TextHandler::handle_method(method),
-            Command::Jwt {method} => JWTHandler::handle_method(method),
-            Command::Regex {method} => RegexHandler::handle_method(method),
-            Command::Lorem {method} => LoremHandler::handle_method(method)
+            Command::Time {method} => TimeHandler::handle_method(method, stdin),
+            Command::Text {method} => TextHandler::handle_method(method, stdin),
+            Command::Jwt {method} => JWTHandler::handle_method(method, stdin),
+            Command::Regex {method} => RegexHandler::handle_method(method, stdin),
+            Command::Lorem {method} => LoremHandler::handle_method(method),
+            Command::Pipe {options} => PipeHandler::handle_method(options, stdin),
+            Command::Shell => Err(CommandHandlerError::InvalidArguments(String::from("The interactive shell can only be started directly with 'dx shell'!"))),
+            Command::Completions {options} => CompletionsHandler::handle_method(options),
+            Command::Man {options} => ManHandler::handle_method(options),
+            Command::Config {method} => ConfigHandler::handle_method(method)
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_exit_codes() {
+        assert_eq!(CommandHandlerError::NegativeResult(String::from("No match found!")).exit_code(), 1);
+        assert_eq!(CommandHandlerError::MissingArguments(vec![String::from("input")]).exit_code(), 2);
+        assert_eq!(CommandHandlerError::RuntimeError(None).exit_code(), 3);
+    }
+}
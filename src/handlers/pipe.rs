@@ -0,0 +1,104 @@
+use std::{mem, result};
+use crate::cli::{Command, PipeOptions};
+use crate::config;
+use crate::handlers::{CommandHandler, CommandHandlerError, CommandOutput, Result};
+use crate::input::InputSource;
+
+pub struct PipeHandler {}
+
+impl PipeHandler {
+    pub fn handle_method(options: &PipeOptions, stdin: &mut InputSource) -> Result {
+        let stages = Self::split_stages(&options.stages)?;
+
+        // Only the first stage reads from the real stdin, the rest receive the previous result
+        let mut input = mem::replace(stdin, InputSource::from_bytes(Vec::new()));
+        let mut output = None;
+        for (stage_i, stage) in stages.iter().enumerate() {
+            let command = Self::parse_stage(stage)
+                .map_err(|err| Self::stage_error(stage_i, stage, err))?;
+
+            let result = CommandHandler::new(command).with_stdin(input).handle()
+                .map_err(|err| Self::stage_error(stage_i, stage, err))?;
+
+            input = InputSource::from_bytes(result.text.as_bytes());
+            output = Some(result);
+        }
+
+        // There is always at least one stage
+        Ok(output.unwrap())
+    }
+
+    /// A single argument is split shell-style, multiple arguments are used as they are
+    fn split_stages(args: &[String]) -> result::Result<Vec<Vec<String>>, CommandHandlerError> {
+        let tokens = match args {
+            [pipeline] => shlex::split(pipeline)
+                .ok_or_else(|| CommandHandlerError::InvalidArguments(format!("Failed to parse pipeline '{pipeline}'! Check for unclosed quotes")))?,
+            args => args.to_vec()
+        };
+
+        let stages = tokens.split(|token| token == "|")
+            .map(|stage| stage.to_vec())
+            .collect::<Vec<Vec<String>>>();
+
+        if stages.iter().any(|stage| stage.is_empty()) {
+            return Err(CommandHandlerError::InvalidArguments(String::from("The pipeline contains an empty operation!")));
+        }
+
+        Ok(stages)
+    }
+
+    fn parse_stage(stage: &[String]) -> result::Result<Command, CommandHandlerError> {
+        let command = config::parse_command_line(stage)
+            .map_err(|err| CommandHandlerError::InvalidArguments(err.render().to_string().trim_end().to_string()))?
+            .command;
+
+        if let Command::Pipe {..} = command {
+            return Err(CommandHandlerError::InvalidArguments(String::from("Pipelines can't be nested!")));
+        }
+
+        Ok(command)
+    }
+
+    /// Adds the failed stage to the error message while keeping the error kind (and exit code)
+    fn stage_error(stage_i: usize, stage: &[String], err: CommandHandlerError) -> CommandHandlerError {
+        let prefix = format!("Stage {} '{}' failed!", stage_i + 1, stage.join(" "));
+        match err {
+            CommandHandlerError::NegativeResult(message) => CommandHandlerError::NegativeResult(format!("{prefix} {message}")),
+            CommandHandlerError::NegativeOutput(output) => CommandHandlerError::NegativeOutput(CommandOutput {
+                data: output.data,
+                text: format!("{prefix} {}", output.text)
+            }),
+            CommandHandlerError::RuntimeError(message) => CommandHandlerError::RuntimeError(Some(
+                format!("{prefix} {}", message.unwrap_or(String::from("A runtime error occurred!")))
+            )),
+            err => CommandHandlerError::InvalidArguments(format!("{prefix} {err}"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use super::*;
+
+    #[test]
+    fn chain_operations() {
+        let result = PipeHandler::handle_method(&PipeOptions {
+            stages: vec![String::from("encode base64 | decode base64 | text upper")]
+        }, &mut InputSource::from_bytes("hello, pipe"));
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().text, "HELLO, PIPE");
+    }
+
+    #[test]
+    fn report_failed_stage() {
+        let result = PipeHandler::handle_method(&PipeOptions {
+            stages: vec![String::from("text upper | decode hex")]
+        }, &mut InputSource::from_bytes("not hex"));
+
+        let err = result.expect_err("Decoding non-hex input should fail");
+        assert!(err.to_string().starts_with("Stage 2 'decode hex' failed!"), "Unexpected error: {err}");
+        assert_eq!(err.exit_code(), 3);
+    }
+}
@@ -1,61 +1,81 @@
-use std::result;
-use md5::digest::typenum::op;
-use regex::Regex;
+use dx::regex;
 use crate::cli::{RegexMatchOptions, RegexMethod, RegexReplaceOptions, RegexTestOptions};
-use crate::handlers::{CommandHandlerError, Result};
+use crate::handlers::{CommandHandlerError, CommandOutput, Result};
+use crate::input::InputSource;
 
 pub struct RegexHandler {}
 
 impl RegexHandler {
-    pub fn handle_method(method: &RegexMethod) -> Result {
+    pub fn handle_method(method: &RegexMethod, stdin: &mut InputSource) -> Result {
         match method {
-            RegexMethod::Test {options} => Self::test_expr(options),
-            RegexMethod::Match {options} => Self::match_expr(options),
-            RegexMethod::Replace {options} => Self::replace_text(options),
+            RegexMethod::Test {options} => Self::test_expr(options, stdin),
+            RegexMethod::Match {options} => Self::match_expr(options, stdin),
+            RegexMethod::Replace {options} => Self::replace_text(options, stdin),
         }
     }
 
-    fn parse_regex(pattern: &str) -> result::Result<Regex, CommandHandlerError> {
-        match Regex::new(pattern) {
-            Ok(re) => Ok(re),
-            Err(_) => Err(CommandHandlerError::RuntimeError(Some(String::from("Invalid RegEx expression!"))))
-        }
-    }
-
-    fn test_expr(options: &RegexTestOptions) -> Result {
-        let re = Self::parse_regex(&options.pattern)?;
-
-        match re.find(&options.text) {
-            Some(_) => Ok(String::from("Match found!")),
+    fn test_expr(options: &RegexTestOptions, stdin: &mut InputSource) -> Result {
+        match regex::first_match(&options.pattern, &options.text.read(stdin)?)? {
+            Some(regex_match) => Ok(CommandOutput::new(&regex_match, String::from("Match found!"))),
             None => Err(CommandHandlerError::NegativeResult(String::from("No match found!")))
         }
     }
 
-    fn match_expr(options: &RegexMatchOptions) -> Result {
-        let re = Self::parse_regex(&options.pattern)?;
+    fn match_expr(options: &RegexMatchOptions, stdin: &mut InputSource) -> Result {
+        let matches = regex::matches(&options.pattern, &options.text.read(stdin)?)?;
+
         let mut result = String::new();
-        for (capture_i, captures) in re.captures_iter(&options.text).enumerate() {
-            if let Some(full_match) = captures.get(0) {
-                result.push_str("------------\n");
-                result.push_str(&format!("Match {}: \"{}\"\n", capture_i, full_match.as_str()));
-                for group_i in 1..captures.len() {
-                    if let Some(group) = captures.get(group_i) {
-                        result.push_str(&format!("\tGroup {}: \"{}\"\n", group_i, group.as_str()))
-                    }
-                }
-                result.push_str("------------\n");
+        for regex_match in &matches {
+            result.push_str("------------\n");
+            result.push_str(&format!("Match {}: \"{}\"\n", regex_match.index, regex_match.text));
+            for group in regex_match.groups.iter().flatten() {
+                result.push_str(&format!("\tGroup {}: \"{}\"\n", group.index, group.text))
             }
+            result.push_str("------------\n");
         }
 
-        if result.is_empty() {
+        if matches.is_empty() {
             return Err(CommandHandlerError::NegativeResult(String::from("No matches found!")));
         }
 
-        return Ok(result);
+        Ok(CommandOutput::new(&matches, result))
     }
 
-    fn replace_text(options: &RegexReplaceOptions) -> Result {
-        let re = Self::parse_regex(&options.pattern)?;
-        return Ok(re.replace_all(&options.text, &options.replacement).to_string());
+    fn replace_text(options: &RegexReplaceOptions, stdin: &mut InputSource) -> Result {
+        Ok(regex::replace(&options.pattern, &options.text.read(stdin)?, &options.replacement)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use crate::input::Input;
+    use super::*;
+
+    #[test]
+    fn structured_regex_matches() {
+        let result = RegexHandler::match_expr(&RegexMatchOptions {
+            pattern: String::from(r"(?<user>\w+)@(\w+)?"),
+            text: Input::from("alice@example bob@"),
+        }, &mut InputSource::from_bytes(""));
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().data, json!([
+            {
+                "index": 0, "start": 0, "end": 13, "text": "alice@example",
+                "groups": [
+                    {"index": 1, "name": "user", "start": 0, "end": 5, "text": "alice"},
+                    {"index": 2, "name": null, "start": 6, "end": 13, "text": "example"}
+                ]
+            },
+            {
+                "index": 1, "start": 14, "end": 18, "text": "bob@",
+                "groups": [
+                    {"index": 1, "name": "user", "start": 14, "end": 17, "text": "bob"},
+                    null
+                ]
+            }
+        ]));
     }
-}
\ No newline at end of file
+}
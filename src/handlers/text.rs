@@ -1,20 +1,24 @@
-use capitalize::Capitalize;
-use stringcase::{camel_case, kebab_case, pascal_case, snake_case};
+use dx::text::{self, Case};
 use crate::handlers::{Result};
-use crate::cli::{TextMethod};
+use crate::cli::{TextMethod, TextOptions};
+use crate::input::InputSource;
 
 pub struct TextHandler {}
 
 impl TextHandler {
-    pub fn handle_method(method: &TextMethod) -> Result {
+    pub fn handle_method(method: &TextMethod, stdin: &mut InputSource) -> Result {
         match method {
-            TextMethod::Upper {options} => Ok(options.text.to_uppercase()),
-            TextMethod::Lower {options} => Ok(options.text.to_lowercase()),
-            TextMethod::Title {options} => Ok(options.text.capitalize()),
-            TextMethod::Camel {options} => Ok(camel_case(&options.text)),
-            TextMethod::Pascal {options} => Ok(pascal_case(&options.text)),
-            TextMethod::Snake {options} => Ok(snake_case(&options.text)),
-            TextMethod::Kebab {options} => Ok(kebab_case(&options.text))
+            TextMethod::Upper {options} => Self::convert(options, Case::Upper, stdin),
+            TextMethod::Lower {options} => Self::convert(options, Case::Lower, stdin),
+            TextMethod::Title {options} => Self::convert(options, Case::Title, stdin),
+            TextMethod::Camel {options} => Self::convert(options, Case::Camel, stdin),
+            TextMethod::Pascal {options} => Self::convert(options, Case::Pascal, stdin),
+            TextMethod::Snake {options} => Self::convert(options, Case::Snake, stdin),
+            TextMethod::Kebab {options} => Self::convert(options, Case::Kebab, stdin)
         }
     }
-}
\ No newline at end of file
+
+    fn convert(options: &TextOptions, case: Case, stdin: &mut InputSource) -> Result {
+        Ok(text::convert(&options.text.read(stdin)?, case).into())
+    }
+}
@@ -1,6 +1,6 @@
-use uuid::{Uuid};
+use dx::uuid::{self, Uuid};
 use crate::cli::{UuidMethod, UuidOptions};
-use crate::handlers::{Result};
+use crate::handlers::{CommandOutput, Result};
 
 pub struct UuidHandler {}
 
@@ -13,20 +13,18 @@ impl UuidHandler {
     }
 
     fn gen_uuid4(options: &UuidOptions) -> Result {
-        let count = if options.number.is_some() {options.number.unwrap()} else {1};
-        let mut result = String::new();
-        for i in 1..=count {
-            result.push_str(&format!("{}. {}\n", i, Uuid::new_v4().to_string()))
-        }
-        Ok(result)
+        Ok(Self::uuid_output(uuid::v4(options.number.unwrap_or(1))))
     }
 
     fn gen_uuid7(options: &UuidOptions) -> Result {
-        let count = if options.number.is_some() {options.number.unwrap()} else {1};
+        Ok(Self::uuid_output(uuid::v7(options.number.unwrap_or(1))))
+    }
+
+    fn uuid_output(uuids: Vec<Uuid>) -> CommandOutput {
         let mut result = String::new();
-        for i in 1..=count {
-            result.push_str(&format!("{}. {}\n", i, Uuid::now_v7().to_string()))
+        for (i, uuid) in uuids.iter().enumerate() {
+            result.push_str(&format!("{}. {}\n", i + 1, uuid))
         }
-        Ok(result)
+        CommandOutput::new(&uuids, result)
     }
-}
\ No newline at end of file
+}
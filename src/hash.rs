@@ -0,0 +1,27 @@
+//! Cryptographic hashes
+
+use md5::{Md5, Digest as Md5Digest};
+use serde::Serialize;
+use sha2::{Sha256, Sha512, Digest as Sha256Digest};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Md5,
+    Sha256,
+    Sha512
+}
+
+/// Hashes the data and returns the digest as lowercase hexadecimal
+pub fn digest(algorithm: HashAlgorithm, data: &[u8]) -> String {
+    match algorithm {
+        HashAlgorithm::Md5 => hex::encode(Md5::digest(data)),
+        HashAlgorithm::Sha256 => hex::encode(Sha256::digest(data)),
+        HashAlgorithm::Sha512 => hex::encode(Sha512::digest(data)),
+    }
+}
+
+/// Checks if the digest of the data matches the expected hexadecimal hash
+pub fn verify(algorithm: HashAlgorithm, data: &[u8], expected: &str) -> bool {
+    digest(algorithm, data) == expected
+}
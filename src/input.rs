@@ -0,0 +1,147 @@
+use std::io::{self, Cursor, IsTerminal, Read};
+use std::{fs, result};
+use crate::handlers::CommandHandlerError;
+
+/// Value of an input argument. Passing `-` or omitting the argument reads the data from stdin.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum Input {
+    #[default]
+    Stdin,
+    Text(String)
+}
+
+impl From<String> for Input {
+    fn from(value: String) -> Self {
+        if value == "-" {
+            return Input::Stdin;
+        }
+        Input::Text(value)
+    }
+}
+
+impl From<&str> for Input {
+    fn from(value: &str) -> Self {
+        Input::from(value.to_string())
+    }
+}
+
+impl Input {
+    /// Reads the raw input bytes without any modifications
+    pub fn read_bytes(&self, source: &mut InputSource) -> result::Result<Vec<u8>, CommandHandlerError> {
+        match self {
+            Input::Text(text) => Ok(text.as_bytes().to_vec()),
+            Input::Stdin => source.read_all()
+        }
+    }
+
+    /// Reads the input as UTF-8 text. A single trailing line break coming from stdin is removed.
+    pub fn read(&self, source: &mut InputSource) -> result::Result<String, CommandHandlerError> {
+        match self {
+            Input::Text(text) => Ok(text.clone()),
+            Input::Stdin => {
+                let mut text = String::from_utf8(source.read_all()?)
+                    .map_err(|_| CommandHandlerError::RuntimeError(Some(String::from("The provided input is not valid UTF-8 text!"))))?;
+
+                if text.ends_with('\n') {
+                    text.pop();
+                    if text.ends_with('\r') {
+                        text.pop();
+                    }
+                }
+                Ok(text)
+            }
+        }
+    }
+}
+
+/// Reads the file (stdin for `-`) or the content argument, falling back to stdin when neither is provided.
+/// Returns the name of the source shown in diagnostics together with the text.
+pub fn read_file_or_content(file: Option<&String>, content: Option<&Input>, stdin: &mut InputSource) -> result::Result<(String, String), CommandHandlerError> {
+    match (file, content) {
+        (Some(file), _) if file != "-" => {
+            let text = fs::read_to_string(file)
+                .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to read '{file}'! {err}"))))?;
+            Ok((file.to_string(), text))
+        },
+        (_, Some(Input::Text(content))) => Ok((String::from("Provided content"), content.to_string())),
+        _ => {
+            let text = Input::Stdin.read(stdin).map_err(|err| match err {
+                CommandHandlerError::MissingArguments(_) => CommandHandlerError::MissingArgumentsSome(vec!["file".to_string(), "content".to_string()]),
+                err => err
+            })?;
+            Ok((String::from("stdin"), text))
+        }
+    }
+}
+
+/// The stream from which `Input::Stdin` arguments are read
+pub struct InputSource {
+    reader: Box<dyn Read>,
+    interactive: bool,
+    /// Stdin was already read by another argument
+    consumed: bool
+}
+
+impl InputSource {
+    pub fn stdin() -> Self {
+        let stdin = io::stdin();
+        InputSource {
+            interactive: stdin.is_terminal(),
+            reader: Box::new(stdin),
+            consumed: false
+        }
+    }
+
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Self {
+        InputSource {
+            reader: Box::new(Cursor::new(bytes.into())),
+            interactive: false,
+            consumed: false
+        }
+    }
+
+    /// The stream itself, for reading large inputs incrementally
+    pub fn reader(&mut self) -> result::Result<&mut dyn Read, CommandHandlerError> {
+        self.take()?;
+        Ok(&mut self.reader)
+    }
+
+    fn read_all(&mut self) -> result::Result<Vec<u8>, CommandHandlerError> {
+        self.take()?;
+        let mut buffer = Vec::new();
+        self.reader.read_to_end(&mut buffer)
+            .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to read from stdin! {err}"))))?;
+        Ok(buffer)
+    }
+
+    /// Marks the stream as read. It can only be read once, so a second argument using it would silently get empty input.
+    fn take(&mut self) -> result::Result<(), CommandHandlerError> {
+        // Waiting for the user to type the input in a terminal is almost never what is intended
+        if self.interactive {
+            return Err(CommandHandlerError::MissingArguments(vec![String::from("input")]));
+        }
+        if self.consumed {
+            return Err(CommandHandlerError::InvalidArguments(String::from("stdin was already read by another argument")));
+        }
+        self.consumed = true;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use crate::utils::TestResult;
+    use super::*;
+
+    #[test]
+    fn read_stdin_only_once() -> TestResult {
+        let mut stdin = InputSource::from_bytes("hello\n");
+        assert_eq!(Input::Stdin.read(&mut stdin)?, "hello");
+
+        let err = Input::Stdin.read(&mut stdin).expect_err("stdin was already read");
+        assert_eq!(err.to_string(), "stdin was already read by another argument");
+        assert_eq!(err.exit_code(), 2);
+        Ok(())
+    }
+}
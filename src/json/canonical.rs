@@ -0,0 +1,57 @@
+//! RFC 8785 JSON Canonicalization Scheme (JCS), a byte-for-byte stable serialization for hashing and signing
+
+use serde_json::{Number, Value};
+use crate::json::minify;
+use crate::{Error, Result};
+
+/// Serializes the value without whitespace, with object keys sorted by their UTF-16 code units and numbers formatted
+/// like ECMAScript does. Numbers are IEEE 754 doubles, so integers beyond 2^53 lose precision as the RFC requires.
+pub fn canonicalize(value: &Value) -> Result<String> {
+    let mut out = String::new();
+    write_value(&mut out, value)?;
+    Ok(out)
+}
+
+fn write_value(out: &mut String, value: &Value) -> Result<()> {
+    match value {
+        Value::Number(number) => out.push_str(&format_number(number)?),
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_value(out, item)?;
+            }
+            out.push(']');
+        },
+        Value::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<(&String, &Value)>>();
+            entries.sort_by_cached_key(|(key, _)| key.encode_utf16().collect::<Vec<u16>>());
+            out.push('{');
+            for (index, (key, value)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&minify(&Value::String(key.clone()))?);
+                out.push(':');
+                write_value(out, value)?;
+            }
+            out.push('}');
+        },
+        // serde_json escapes strings the way the RFC requires: only quotes, backslashes and control characters
+        value => out.push_str(&minify(value)?)
+    }
+    Ok(())
+}
+
+fn format_number(number: &Number) -> Result<String> {
+    let value = number.as_f64()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| Error::InvalidInput(format!("The number {number} can't be canonicalized!")))?;
+    if value == 0.0 {
+        // Also covers -0
+        return Ok(String::from("0"));
+    }
+    Ok(ryu_js::Buffer::new().format_finite(value).to_string())
+}
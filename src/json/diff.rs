@@ -0,0 +1,167 @@
+//! Structural differences between two JSON documents, optionally as an RFC 6902 JSON Patch
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use crate::json::pointer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed
+}
+
+/// A single difference, addressed by a JSON Pointer
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub pointer: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<Value>,
+    /// The value is an item of an array, not a member of an object
+    #[serde(skip)]
+    pub in_array: bool
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// Compare arrays as multisets. Moved items aren't reported.
+    pub ignore_array_order: bool,
+    /// Object keys ignored at any depth, or JSON Pointers (starting with `/`) ignored at exactly that location
+    pub ignore_keys: Vec<String>
+}
+
+/// Returns the changes which turn `old` into `new` in document order
+pub fn diff(old: &Value, new: &Value, options: &DiffOptions) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_values(old, new, "", false, options, &mut changes);
+    changes
+}
+
+/// Converts the changes to RFC 6902 operations. Array items removed with `ignore_array_order` are removed from the
+/// highest index down and added items are appended.
+pub fn to_patch(changes: &[Change], options: &DiffOptions) -> Value {
+    let operations = changes.iter()
+        .map(|change| match change.kind {
+            ChangeKind::Added if options.ignore_array_order && change.in_array => json!({
+                "op": "add",
+                "path": format!("{}/-", parent(&change.pointer)),
+                "value": change.new
+            }),
+            ChangeKind::Added => json!({"op": "add", "path": change.pointer, "value": change.new}),
+            ChangeKind::Removed => json!({"op": "remove", "path": change.pointer}),
+            ChangeKind::Changed => json!({"op": "replace", "path": change.pointer, "value": change.new})
+        })
+        .collect();
+    Value::Array(operations)
+}
+
+fn diff_values(old: &Value, new: &Value, path: &str, in_array: bool, options: &DiffOptions, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            for (key, old_value) in old_map {
+                let child_path = format!("{path}/{}", pointer::escape(key));
+                if is_ignored(key, &child_path, options) {
+                    continue;
+                }
+                match new_map.get(key) {
+                    Some(new_value) => diff_values(old_value, new_value, &child_path, false, options, changes),
+                    None => changes.push(removed(child_path, old_value, false))
+                }
+            }
+            for (key, new_value) in new_map {
+                let child_path = format!("{path}/{}", pointer::escape(key));
+                if !old_map.contains_key(key) && !is_ignored(key, &child_path, options) {
+                    changes.push(added(child_path, new_value, false));
+                }
+            }
+        },
+        (Value::Array(old_items), Value::Array(new_items)) if options.ignore_array_order => {
+            let mut matched = vec![false; new_items.len()];
+            let mut removed_items = Vec::new();
+            for (old_i, old_item) in old_items.iter().enumerate() {
+                let found = new_items.iter().enumerate()
+                    .position(|(new_i, new_item)| !matched[new_i] && diff(old_item, new_item, options).is_empty());
+                match found {
+                    Some(new_i) => matched[new_i] = true,
+                    None => removed_items.push(removed(format!("{path}/{old_i}"), old_item, true))
+                }
+            }
+            // Removing from the end keeps the remaining indexes valid when the changes are applied in order
+            changes.extend(removed_items.into_iter().rev());
+            for (new_i, new_item) in new_items.iter().enumerate() {
+                if !matched[new_i] {
+                    changes.push(added(format!("{path}/{new_i}"), new_item, true));
+                }
+            }
+        },
+        (Value::Array(old_items), Value::Array(new_items)) => {
+            for (index, (old_item, new_item)) in old_items.iter().zip(new_items).enumerate() {
+                diff_values(old_item, new_item, &format!("{path}/{index}"), true, options, changes);
+            }
+            for index in (new_items.len()..old_items.len()).rev() {
+                changes.push(removed(format!("{path}/{index}"), &old_items[index], true));
+            }
+            for (index, new_item) in new_items.iter().enumerate().skip(old_items.len()) {
+                changes.push(added(format!("{path}/{index}"), new_item, true));
+            }
+        },
+        (old, new) if old != new => changes.push(Change {
+            kind: ChangeKind::Changed,
+            pointer: path.to_string(),
+            old: Some(old.clone()),
+            new: Some(new.clone()),
+            in_array
+        }),
+        _ => {}
+    }
+}
+
+fn is_ignored(key: &str, path: &str, options: &DiffOptions) -> bool {
+    options.ignore_keys.iter().any(|ignored| if ignored.starts_with('/') {ignored == path} else {ignored == key})
+}
+
+fn parent(pointer: &str) -> &str {
+    pointer.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+fn added(pointer: String, value: &Value, in_array: bool) -> Change {
+    Change {kind: ChangeKind::Added, pointer, old: None, new: Some(value.clone()), in_array}
+}
+
+fn removed(pointer: String, value: &Value, in_array: bool) -> Change {
+    Change {kind: ChangeKind::Removed, pointer, old: Some(value.clone()), new: None, in_array}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::{assert_eq};
+
+    #[test]
+    fn append_only_array_items() {
+        let options = DiffOptions {ignore_array_order: true, ..DiffOptions::default()};
+        let old = json!({"m": {}, "tags": ["a"]});
+        let new = json!({"m": {"1": "x"}, "tags": ["b", "a"]});
+
+        assert_eq!(to_patch(&diff(&old, &new, &options), &options), json!([
+            {"op": "add", "path": "/m/1", "value": "x"},
+            {"op": "add", "path": "/tags/-", "value": "b"}
+        ]));
+    }
+
+    #[test]
+    fn ignore_order_and_keys() {
+        let options = DiffOptions {ignore_array_order: true, ignore_keys: vec![String::from("updated")]};
+        let old = json!({"id": 1, "tags": ["a", "b", "c"], "meta": {"updated": "2024-01-01"}});
+        let new = json!({"id": 2, "tags": ["c", "a"], "meta": {"updated": "2025-01-01"}});
+
+        assert_eq!(to_patch(&diff(&old, &new, &options), &options), json!([
+            {"op": "replace", "path": "/id", "value": 2},
+            {"op": "remove", "path": "/tags/1"}
+        ]));
+    }
+}
@@ -0,0 +1,256 @@
+//! Flattening nested JSON into a single level object keyed by paths like `a.b[0].c`, and rebuilding it
+
+use serde_json::{Map, Value};
+use crate::{Error, Result};
+
+/// How array indices are written in the paths
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ArrayNotation {
+    /// `tags[0]`
+    #[default]
+    Brackets,
+    /// `tags.0`, using the separator. Numeric keys are read back as array indices
+    Index
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathStyle {
+    /// Put between object keys, `.` by default
+    pub separator: String,
+    pub arrays: ArrayNotation
+}
+
+impl Default for PathStyle {
+    fn default() -> Self {
+        PathStyle {separator: String::from("."), arrays: ArrayNotation::default()}
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize)
+}
+
+/// Maps the path of every scalar to its value, in document order. Empty objects and arrays are kept as values,
+/// so unflattening gives back the same document, unless a key contains the separator or looks like an array index
+/// (ends with `[0]`, or is a number with `ArrayNotation::Index`): `{"a.b": 1}` unflattens to `{"a": {"b": 1}}`.
+/// A scalar document gets the empty path.
+/// Fails when two values get the same path, e.g. `{"a.b": 1, "a": {"b": 2}}` with the `.` separator.
+pub fn flatten(value: &Value, style: &PathStyle) -> Result<Map<String, Value>> {
+    check(style)?;
+    let mut flat = Map::new();
+    flatten_into(value, String::new(), style, &mut flat)?;
+    Ok(flat)
+}
+
+fn flatten_into(value: &Value, path: String, style: &PathStyle, flat: &mut Map<String, Value>) -> Result<()> {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                let path = if path.is_empty() {key.clone()} else {format!("{path}{}{key}", style.separator)};
+                flatten_into(value, path, style, flat)?;
+            }
+        },
+        Value::Array(items) if !items.is_empty() => {
+            for (index, item) in items.iter().enumerate() {
+                let path = match style.arrays {
+                    ArrayNotation::Brackets => format!("{path}[{index}]"),
+                    ArrayNotation::Index if path.is_empty() => index.to_string(),
+                    ArrayNotation::Index => format!("{path}{}{index}", style.separator)
+                };
+                flatten_into(item, path, style, flat)?;
+            }
+        },
+        value => {
+            if flat.contains_key(&path) {
+                return Err(Error::InvalidInput(format!("More than one value has the path '{path}', try a different separator!")));
+            }
+            flat.insert(path, value.clone());
+        }
+    }
+    Ok(())
+}
+
+/// Largest array index [`unflatten`] accepts, as every missing item before it is filled in
+pub const MAX_INDEX: usize = 100_000;
+
+/// Rebuilds the nested document from an object of paths, the reverse of [`flatten`]. Missing array items become `null`,
+/// indices above [`MAX_INDEX`] are an error.
+pub fn unflatten(flat: &Value, style: &PathStyle) -> Result<Value> {
+    check(style)?;
+    let Value::Object(flat) = flat else {
+        return Err(Error::InvalidInput(String::from("Only an object of paths and values can be unflattened!")));
+    };
+
+    let mut root = None;
+    for (path, value) in flat {
+        let segments = parse_path(path, style);
+        if segments.iter().any(|segment| matches!(segment, Segment::Index(index) if *index > MAX_INDEX)) {
+            return Err(Error::InvalidInput(format!("The array index in '{path}' is larger than {MAX_INDEX}!")));
+        }
+        if !insert(&mut root, &segments, value.clone()) {
+            return Err(Error::InvalidInput(format!("The path '{path}' conflicts with another path!")));
+        }
+    }
+    Ok(root.unwrap_or_else(|| Value::Object(Map::new())))
+}
+
+fn check(style: &PathStyle) -> Result<()> {
+    if style.separator.is_empty() {
+        return Err(Error::InvalidInput(String::from("The separator can't be empty!")));
+    }
+    Ok(())
+}
+
+fn parse_path(path: &str, style: &PathStyle) -> Vec<Segment> {
+    if path.is_empty() {
+        return Vec::new();
+    }
+    let mut segments = Vec::new();
+    for part in path.split(style.separator.as_str()) {
+        match style.arrays {
+            ArrayNotation::Index => segments.push(match part.parse::<usize>() {
+                Ok(index) if part == index.to_string() => Segment::Index(index),
+                _ => Segment::Key(part.to_string())
+            }),
+            ArrayNotation::Brackets => {
+                // Trailing `[n]` suffixes are indices, anything else is part of the key
+                let mut key = part;
+                let mut indices = Vec::new();
+                while let Some(rest) = key.strip_suffix(']') {
+                    let Some((before, index)) = rest.rsplit_once('[') else {
+                        break;
+                    };
+                    match index.parse::<usize>() {
+                        Ok(parsed) if index == parsed.to_string() => {
+                            indices.push(parsed);
+                            key = before;
+                        },
+                        _ => break
+                    }
+                }
+                // `[0]` of a top-level array has no key before it
+                if !(key.is_empty() && segments.is_empty() && !indices.is_empty()) {
+                    segments.push(Segment::Key(key.to_string()));
+                }
+                segments.extend(indices.into_iter().rev().map(Segment::Index));
+            }
+        }
+    }
+    segments
+}
+
+/// Sets the value at the path, creating objects and arrays on the way. Returns `false` when a value is already in
+/// the way, e.g. for `a.b` after `a`.
+fn insert(target: &mut Option<Value>, segments: &[Segment], value: Value) -> bool {
+    let Some((segment, rest)) = segments.split_first() else {
+        if target.is_some() {
+            return false;
+        }
+        *target = Some(value);
+        return true;
+    };
+
+    let slot = target.get_or_insert_with(|| match segment {
+        Segment::Key(_) => Value::Object(Map::new()),
+        Segment::Index(_) => Value::Array(Vec::new())
+    });
+    match (slot, segment) {
+        (Value::Object(map), Segment::Key(key)) => match map.get_mut(key) {
+            Some(existing) => {
+                let mut child = Some(std::mem::take(existing));
+                let inserted = insert(&mut child, rest, value);
+                *existing = child.unwrap_or_default();
+                inserted
+            },
+            None => {
+                let mut child = None;
+                let inserted = insert(&mut child, rest, value);
+                if let Some(child) = child {
+                    map.insert(key.clone(), child);
+                }
+                inserted
+            }
+        },
+        (Value::Array(items), Segment::Index(index)) => {
+            if items.len() <= *index {
+                items.resize(index + 1, Value::Null);
+            }
+            // Nulls padding the array until its later items are set can still be replaced
+            let mut child = Some(std::mem::take(&mut items[*index])).filter(|child| !child.is_null());
+            let inserted = insert(&mut child, rest, value);
+            items[*index] = child.unwrap_or_default();
+            inserted
+        },
+        _ => false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+    use pretty_assertions::{assert_eq};
+
+    #[test]
+    fn reject_colliding_paths() {
+        let document = json!({"a.b": 1, "a": {"b": 2}});
+        let err = flatten(&document, &PathStyle::default()).expect_err("Both values have the path 'a.b'");
+        assert!(err.to_string().contains("'a.b'"), "Unexpected error: {err}");
+
+        let style = PathStyle {separator: String::from("/"), ..PathStyle::default()};
+        assert_eq!(Value::Object(flatten(&document, &style).unwrap()), json!({"a.b": 1, "a/b": 2}));
+    }
+
+    #[test]
+    fn nest_keys_containing_the_separator() -> Result<()> {
+        let style = PathStyle::default();
+        let flat = flatten(&json!({"a.b": 1, "c[0]": 2}), &style)?;
+        assert_eq!(unflatten(&Value::Object(flat), &style)?, json!({"a": {"b": 1}, "c": [2]}));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_bracket_paths() {
+        let style = PathStyle::default();
+        assert_eq!(parse_path("a[0][1].b", &style), vec![
+            Segment::Key(String::from("a")),
+            Segment::Index(0),
+            Segment::Index(1),
+            Segment::Key(String::from("b"))
+        ]);
+        assert_eq!(parse_path("[2]", &style), vec![Segment::Index(2)]);
+        // Only canonical numbers are indices, anything else stays part of the key
+        assert_eq!(parse_path("a[01]", &style), vec![Segment::Key(String::from("a[01]"))]);
+        assert_eq!(parse_path("a[b][0]", &style), vec![Segment::Key(String::from("a[b]")), Segment::Index(0)]);
+
+        let unflattened = unflatten(&json!({"a[0][1]": 1, "a[0][0]": 0, "a[1]": null}), &style).unwrap();
+        assert_eq!(unflattened, json!({"a": [[0, 1], null]}));
+    }
+
+    #[test]
+    fn reject_conflicting_paths() {
+        let style = PathStyle::default();
+        for flat in [json!({"a": 1, "a.b": 2}), json!({"a.b": 1, "a": 2}), json!({"[0]": 1, "a": 2}), json!({"a[0]": 1, "a.b": 2})] {
+            let err = unflatten(&flat, &style).expect_err("The paths conflict");
+            assert!(err.to_string().contains("conflicts with another path"), "Unexpected error: {err}");
+        }
+    }
+
+    #[test]
+    fn fill_sparse_indices_with_null() {
+        let style = PathStyle::default();
+        assert_eq!(unflatten(&json!({"a[5]": 1}), &style).unwrap(), json!({"a": [null, null, null, null, null, 1]}));
+        assert_eq!(unflatten(&json!({"a[1]": 1, "a[0]": 2}), &style).unwrap(), json!({"a": [2, 1]}));
+    }
+
+    #[test]
+    fn reject_indices_above_the_maximum() {
+        let style = PathStyle::default();
+        assert!(unflatten(&json!({format!("a[{MAX_INDEX}]"): 1}), &style).is_ok());
+
+        let err = unflatten(&json!({"a[100001]": 1}), &style).expect_err("The index is above the maximum");
+        assert!(err.to_string().contains("'a[100001]' is larger than 100000"), "Unexpected error: {err}");
+    }
+}
@@ -0,0 +1,192 @@
+//! Inferring the shape of JSON documents from sample values
+
+use std::sync::LazyLock;
+use ::regex::Regex;
+use chrono::{DateTime, NaiveDate};
+use serde_json::Value;
+
+/// Most distinct values a string field can have to be treated as an enum
+pub const ENUM_LIMIT: usize = 5;
+
+/// The merged shape of every value seen at one position
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Shape {
+    /// `null` was seen
+    pub nullable: bool,
+    /// `None` when only nulls (or nothing, e.g. the items of empty arrays) were seen
+    pub kind: Option<Kind>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    Bool,
+    Integer(Integers),
+    /// Numbers with a fraction, or a mix of integers and fractions
+    Number,
+    String(Strings),
+    Array(Box<Shape>),
+    Object(Object),
+    /// Values of different types
+    Mixed
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Integers {
+    /// A value above `i64::MAX` was seen
+    pub above_i64: bool,
+    /// A negative value was seen
+    pub negative: bool
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Strings {
+    /// Distinct values in order of appearance, `None` once there are more than [`ENUM_LIMIT`]
+    pub values: Option<Vec<String>>,
+    /// Number of strings seen
+    pub count: usize,
+    /// Format every string matches
+    pub format: Option<StringFormat>
+}
+
+impl Strings {
+    /// Distinct values of a small set that repeats, so a field holding names or IDs doesn't become an enum
+    pub fn enumeration(&self) -> Option<&[String]> {
+        self.values.as_deref().filter(|values| self.format.is_none() && self.count > values.len())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StringFormat {
+    DateTime,
+    Date,
+    Uuid,
+    Email
+}
+
+impl StringFormat {
+    /// The name of the format in JSON Schema
+    pub fn name(&self) -> &'static str {
+        match self {
+            StringFormat::DateTime => "date-time",
+            StringFormat::Date => "date",
+            StringFormat::Uuid => "uuid",
+            StringFormat::Email => "email"
+        }
+    }
+
+    fn detect(text: &str) -> Option<StringFormat> {
+        if DateTime::parse_from_rfc3339(text).is_ok() {
+            Some(StringFormat::DateTime)
+        } else if text.len() == 10 && NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok() {
+            Some(StringFormat::Date)
+        } else if text.len() == 36 && uuid::Uuid::try_parse(text).is_ok() {
+            Some(StringFormat::Uuid)
+        } else if is_email(text) {
+            Some(StringFormat::Email)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object {
+    /// Fields in order of appearance
+    pub fields: Vec<Field>,
+    /// Number of objects seen
+    pub count: usize
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub shape: Shape,
+    /// Number of objects the field was in
+    pub count: usize
+}
+
+impl Object {
+    /// Whether some of the objects didn't have the field
+    pub fn is_optional(&self, field: &Field) -> bool {
+        field.count < self.count
+    }
+}
+
+/// Infers the shape of a single value
+pub fn infer(value: &Value) -> Shape {
+    let kind = match value {
+        Value::Null => return Shape {nullable: true, kind: None},
+        Value::Bool(_) => Kind::Bool,
+        Value::Number(number) if number.is_f64() => Kind::Number,
+        Value::Number(number) => Kind::Integer(Integers {
+            above_i64: number.as_i64().is_none(),
+            negative: number.as_i64().is_some_and(|integer| integer < 0)
+        }),
+        Value::String(text) => Kind::String(Strings {values: Some(vec![text.clone()]), count: 1, format: StringFormat::detect(text)}),
+        Value::Array(items) => Kind::Array(Box::new(infer_all(items))),
+        Value::Object(map) => Kind::Object(Object {
+            fields: map.iter().map(|(name, value)| Field {name: name.clone(), shape: infer(value), count: 1}).collect(),
+            count: 1
+        })
+    };
+    Shape {nullable: false, kind: Some(kind)}
+}
+
+/// Infers the shape that every one of the values fits
+pub fn infer_all<'a>(values: impl IntoIterator<Item = &'a Value>) -> Shape {
+    values.into_iter().map(infer).fold(Shape::default(), merge)
+}
+
+/// Merges two shapes into one that fits the values of both
+pub fn merge(a: Shape, b: Shape) -> Shape {
+    let kind = match (a.kind, b.kind) {
+        (None, kind) | (kind, None) => kind,
+        (Some(a), Some(b)) => Some(merge_kinds(a, b))
+    };
+    Shape {nullable: a.nullable || b.nullable, kind}
+}
+
+fn merge_kinds(a: Kind, b: Kind) -> Kind {
+    match (a, b) {
+        (Kind::Bool, Kind::Bool) => Kind::Bool,
+        (Kind::Integer(a), Kind::Integer(b)) => Kind::Integer(Integers {above_i64: a.above_i64 || b.above_i64, negative: a.negative || b.negative}),
+        (Kind::Integer(_) | Kind::Number, Kind::Integer(_) | Kind::Number) => Kind::Number,
+        (Kind::String(a), Kind::String(b)) => {
+            let values = match (a.values, b.values) {
+                (Some(mut values), Some(other)) => {
+                    for value in other {
+                        if !values.contains(&value) {
+                            values.push(value);
+                        }
+                    }
+                    Some(values).filter(|values| values.len() <= ENUM_LIMIT)
+                },
+                _ => None
+            };
+            let format = a.format.filter(|format| b.format == Some(*format));
+            Kind::String(Strings {values, count: a.count + b.count, format})
+        },
+        (Kind::Array(a), Kind::Array(b)) => Kind::Array(Box::new(merge(*a, *b))),
+        (Kind::Object(mut a), Kind::Object(b)) => {
+            for field in b.fields {
+                match a.fields.iter().position(|existing| existing.name == field.name) {
+                    Some(index) => {
+                        let existing = &mut a.fields[index];
+                        existing.shape = merge(std::mem::take(&mut existing.shape), field.shape);
+                        existing.count += field.count;
+                    },
+                    None => a.fields.push(field)
+                }
+            }
+            Kind::Object(Object {fields: a.fields, count: a.count + b.count})
+        },
+        _ => Kind::Mixed
+    }
+}
+
+fn is_email(text: &str) -> bool {
+    static EMAIL: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"^[^@\s]+@[^@\s.]+(\.[^@\s.]+)+$").expect("The email pattern is valid")
+    });
+    EMAIL.is_match(text)
+}
@@ -0,0 +1,259 @@
+//! Parsing, formatting, minifying, querying, editing, patching, validating and comparing JSON documents
+
+pub mod canonical;
+pub mod diff;
+pub mod flatten;
+pub mod infer;
+pub mod patch;
+pub mod path;
+pub mod pointer;
+pub mod query;
+pub mod schema;
+pub mod source;
+pub mod stream;
+pub mod syntax;
+pub mod types;
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use miette::{Diagnostic, LabeledSpan, NamedSource, SourceSpan};
+use crate::json::syntax::SyntaxError;
+use serde_json::Value;
+use crate::Result;
+
+/// JSON syntax error carrying the source document so miette can underline the offending spans
+#[derive(Debug, Diagnostic)]
+pub struct JSONParseError {
+    /// `None` for empty input, which has nothing to underline
+    #[source_code]
+    src: Option<NamedSource<String>>,
+
+    #[label(collection)]
+    labels: Vec<LabeledSpan>,
+
+    #[help]
+    help: Option<String>,
+
+    /// Every error is a construct JSON5 accepts, so the document can be read with `parse_lenient`
+    json5: bool,
+
+    message: String
+}
+
+impl JSONParseError {
+    pub fn new(src_name: &str, src: String, err_span: SourceSpan, message: String) -> Self {
+        JSONParseError {
+            src: Some(NamedSource::new(src_name, src)),
+            labels: vec![LabeledSpan::new_with_span(Some(String::from("error occurred here")), err_span)],
+            help: None,
+            json5: false,
+            message
+        }
+    }
+
+    /// A single report labelling every error, with the hints of the common mistakes as help
+    pub fn from_syntax_errors(src_name: &str, src: String, errors: &[SyntaxError]) -> Self {
+        let labels = errors.iter()
+            .map(|error| LabeledSpan::new_with_span(Some(error.message.clone()), SourceSpan::from(error.span.clone())))
+            .collect();
+
+        let mut hints = Vec::new();
+        for hint in errors.iter().filter_map(|error| error.hint) {
+            if !hints.contains(&hint.message()) {
+                hints.push(hint.message());
+            }
+        }
+        let json5 = errors.iter().all(|error| error.hint.is_some_and(|hint| hint.is_json5()));
+        if json5 {
+            hints.push(if errors.len() == 1 {"JSON5 allows this"} else {"JSON5 allows all of these"});
+        }
+
+        let message = match errors {
+            [error] => format!("Failed to parse JSON: {}", error.message),
+            errors => format!("Failed to parse JSON: found {} syntax errors", errors.len())
+        };
+        JSONParseError {
+            src: (!src.is_empty()).then(|| NamedSource::new(src_name, src)),
+            labels,
+            help: (!hints.is_empty()).then(|| hints.join("\n")),
+            json5,
+            message
+        }
+    }
+
+    /// Whether the document only fails because of constructs JSON5 accepts
+    pub fn is_json5(&self) -> bool {
+        self.json5
+    }
+
+    /// Adds a line to the help shown below the labelled source
+    pub fn add_help(&mut self, line: &str) {
+        self.help = Some(match self.help.take() {
+            Some(help) => format!("{help}\n{line}"),
+            None => line.to_string()
+        });
+    }
+}
+
+impl Display for JSONParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for JSONParseError {}
+
+/// Indentation of formatted JSON
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Indent {
+    Spaces(usize),
+    Tabs
+}
+
+/// Layout used by `format`. The default gives the same output as `to_pretty`
+#[derive(Debug, Clone)]
+pub struct Style {
+    pub indent: Indent,
+    /// Write object keys in alphabetical order at every level instead of their original order
+    pub sort_keys: bool,
+    /// Write arrays containing only scalars on a single line
+    pub compact_arrays: bool
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {indent: Indent::Spaces(2), sort_keys: false, compact_arrays: false}
+    }
+}
+
+/// Parses a JSON document. `src_name` is the name of the source (e.g. file path) shown in diagnostics.
+/// Every syntax error is reported, not only the first one.
+pub fn parse(src: &str, src_name: &str) -> Result<Value> {
+    serde_json::from_str(src)
+        .map_err(|err| {
+            let errors = syntax::check(src);
+            if !errors.is_empty() {
+                return JSONParseError::from_syntax_errors(src_name, src.to_string(), &errors).into();
+            }
+
+            // Valid syntax which serde_json still rejects, e.g. a number out of range. Its column counts bytes.
+            let mut offset = offset(src, err.line(), 1) + err.column().saturating_sub(1);
+            offset = offset.min(src.len());
+            while !src.is_char_boundary(offset) {
+                offset -= 1;
+            }
+            JSONParseError::new(
+                src_name,
+                src.to_string(),
+                SourceSpan::new(offset.into(), 0),
+                format!("Failed to parse JSON: {err}")
+            ).into()
+        })
+}
+
+/// Serializes the value as indented JSON
+pub fn to_pretty(value: &Value) -> Result<String> {
+    serde_json::to_string_pretty(value)
+        .map_err(|err| crate::Error::InvalidInput(format!("Failed to prettify JSON! {}", err)))
+}
+
+/// Serializes the value as JSON without any whitespace
+pub fn minify(value: &Value) -> Result<String> {
+    serde_json::to_string(value)
+        .map_err(|err| crate::Error::InvalidInput(format!("Failed to minify JSON! {}", err)))
+}
+
+/// Parses a JSON5 document, which allows comments, trailing commas, single quoted strings, unquoted keys and more.
+/// Every JSON document is also a JSON5 document.
+pub fn parse_lenient(src: &str, src_name: &str) -> Result<Value> {
+    json5::from_str(src).map_err(|err| match err {
+        json5::Error::Message {msg, location} => {
+            let offset = location.map_or(0, |location| offset(src, location.line, location.column)).min(src.len());
+            // pest renders the source itself, only its summary at the end is kept
+            let message = msg.lines().last().unwrap_or_default().trim().trim_start_matches("= ").to_string();
+            JSONParseError::new(
+                src_name,
+                src.to_string(),
+                SourceSpan::new(offset.into(), 0),
+                format!("Failed to parse JSON5: {message}")
+            ).into()
+        }
+    })
+}
+
+/// Pretty prints the value with the given style
+pub fn format(value: &Value, style: &Style) -> Result<String> {
+    let mut out = String::new();
+    write_value(&mut out, value, style, 0)?;
+    Ok(out)
+}
+
+/// Sorts the keys of all objects in the value alphabetically
+pub fn sort_keys(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.sort_keys();
+            map.values_mut().for_each(sort_keys);
+        },
+        Value::Array(items) => items.iter_mut().for_each(sort_keys),
+        _ => {}
+    }
+}
+
+fn write_value(out: &mut String, value: &Value, style: &Style, depth: usize) -> Result<()> {
+    match value {
+        Value::Array(items) if items.is_empty() => out.push_str("[]"),
+        Value::Object(map) if map.is_empty() => out.push_str("{}"),
+        Value::Array(items) if style.compact_arrays && items.iter().all(|item| !item.is_array() && !item.is_object()) => {
+            let items = items.iter().map(minify).collect::<Result<Vec<String>>>()?;
+            out.push_str(&format!("[{}]", items.join(", ")));
+        },
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                out.push_str(if index == 0 {"\n"} else {",\n"});
+                write_indent(out, style, depth + 1);
+                write_value(out, item, style, depth + 1)?;
+            }
+            out.push('\n');
+            write_indent(out, style, depth);
+            out.push(']');
+        },
+        Value::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<(&String, &Value)>>();
+            if style.sort_keys {
+                entries.sort_by_key(|(key, _)| *key);
+            }
+            out.push('{');
+            for (index, (key, value)) in entries.into_iter().enumerate() {
+                out.push_str(if index == 0 {"\n"} else {",\n"});
+                write_indent(out, style, depth + 1);
+                out.push_str(&minify(&Value::String(key.clone()))?);
+                out.push_str(": ");
+                write_value(out, value, style, depth + 1)?;
+            }
+            out.push('\n');
+            write_indent(out, style, depth);
+            out.push('}');
+        },
+        value => out.push_str(&minify(value)?)
+    }
+    Ok(())
+}
+
+fn write_indent(out: &mut String, style: &Style, depth: usize) {
+    match style.indent {
+        Indent::Spaces(size) => out.push_str(&" ".repeat(size * depth)),
+        Indent::Tabs => out.push_str(&"\t".repeat(depth))
+    }
+}
+
+/// Byte offset of a one-based line and column, with the column counted in characters
+fn offset(src: &str, line: usize, column: usize) -> usize {
+    let Some(line_text) = src.split_inclusive('\n').nth(line.saturating_sub(1)) else {
+        return src.len();
+    };
+    let line_start = line_text.as_ptr() as usize - src.as_ptr() as usize;
+    let column_offset = line_text.char_indices().nth(column.saturating_sub(1)).map_or(line_text.len(), |(index, _)| index);
+    line_start + column_offset
+}
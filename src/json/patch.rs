@@ -0,0 +1,169 @@
+//! Applying RFC 6902 JSON Patches and RFC 7396 JSON Merge Patches
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use crate::json::pointer;
+use crate::{Error, Result};
+
+/// A single JSON Patch operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum Operation {
+    Add {path: String, value: Value},
+    Remove {path: String},
+    Replace {path: String, value: Value},
+    Move {from: String, path: String},
+    Copy {from: String, path: String},
+    Test {path: String, value: Value}
+}
+
+impl Operation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Add {..} => "add",
+            Operation::Remove {..} => "remove",
+            Operation::Replace {..} => "replace",
+            Operation::Move {..} => "move",
+            Operation::Copy {..} => "copy",
+            Operation::Test {..} => "test"
+        }
+    }
+}
+
+/// Result of applying a JSON Patch
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    Patched(Value),
+    /// A `test` operation didn't match, so none of the operations were applied
+    TestFailed {index: usize, path: String, expected: Value, actual: Option<Value>}
+}
+
+/// Reads the operations of a JSON Patch document
+pub fn parse(patch: &Value) -> Result<Vec<Operation>> {
+    let Value::Array(operations) = patch else {
+        return Err(Error::InvalidInput(String::from("A JSON Patch must be an array of operations!")));
+    };
+
+    operations.iter()
+        .enumerate()
+        .map(|(index, operation)| serde_json::from_value(operation.clone())
+            .map_err(|err| Error::InvalidInput(format!("Operation {index} of the JSON Patch is invalid! {err}"))))
+        .collect()
+}
+
+/// Applies the operations in order. The patch is atomic, so the document is only returned if all of them succeeded.
+pub fn apply(document: &Value, operations: &[Operation]) -> Result<Outcome> {
+    let mut patched = document.clone();
+    for (index, operation) in operations.iter().enumerate() {
+        let failed = |err: Error| Error::InvalidInput(format!("Operation {index} ({}) failed! {err}", operation.name()));
+        match operation {
+            Operation::Add {path, value} => pointer::insert(&mut patched, path, value.clone()).map_err(failed)?,
+            Operation::Remove {path} => {
+                pointer::delete(&mut patched, path).map_err(failed)?;
+            },
+            Operation::Replace {path, value} => {
+                let target = pointer::get_mut(&mut patched, path).map_err(failed)?.ok_or_else(|| failed(not_found(path)))?;
+                *target = value.clone();
+            },
+            Operation::Move {from, path} => {
+                if path.starts_with(&format!("{from}/")) {
+                    return Err(failed(Error::InvalidInput(format!("'{from}' can't be moved into its own child '{path}'!"))));
+                }
+                if from != path {
+                    let value = pointer::delete(&mut patched, from).map_err(failed)?;
+                    pointer::insert(&mut patched, path, value).map_err(failed)?;
+                }
+            },
+            Operation::Copy {from, path} => {
+                let value = pointer::get(&patched, from).map_err(failed)?.cloned().ok_or_else(|| failed(not_found(from)))?;
+                pointer::insert(&mut patched, path, value).map_err(failed)?;
+            },
+            Operation::Test {path, value} => {
+                let actual = pointer::get(&patched, path).map_err(failed)?;
+                if !actual.is_some_and(|actual| equal(actual, value)) {
+                    return Ok(Outcome::TestFailed {index, path: path.clone(), expected: value.clone(), actual: actual.cloned()});
+                }
+            }
+        }
+    }
+    Ok(Outcome::Patched(patched))
+}
+
+/// Applies an RFC 7396 JSON Merge Patch: objects are merged recursively, `null` removes a member and any other value
+/// replaces the target
+pub fn merge(target: &mut Value, patch: &Value) {
+    let Value::Object(patch_map) = patch else {
+        *target = patch.clone();
+        return;
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target_map) = target {
+        for (key, value) in patch_map {
+            if value.is_null() {
+                target_map.shift_remove(key);
+            } else {
+                merge(target_map.entry(key.as_str()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// Same as `==`, except that numbers are compared by value, so `1` equals `1.0`. Two integers are compared exactly,
+/// as large ones can round to the same float
+fn equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a == b || ((a.is_f64() || b.is_f64()) && a.as_f64() == b.as_f64()),
+        (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| equal(a, b)),
+        (Value::Object(a), Value::Object(b)) => a.len() == b.len() && a.iter().all(|(key, a)| b.get(key).is_some_and(|b| equal(a, b))),
+        (a, b) => a == b
+    }
+}
+
+fn not_found(pointer: &str) -> Error {
+    Error::InvalidInput(format!("Nothing found at JSON Pointer '{pointer}'!"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+    use pretty_assertions::{assert_eq};
+
+    #[test]
+    fn apply_atomically() -> Result<()> {
+        let document = json!({"name": "dx", "tags": ["cli"], "version": 1});
+
+        let err = apply(&document, &parse(&json!([
+            {"op": "copy", "from": "/name", "path": "/tags/0"},
+            {"op": "move", "from": "/version", "path": "/meta/version"}
+        ]))?).expect_err("/meta doesn't exist");
+        assert!(err.to_string().starts_with("Operation 1 (move) failed!"), "Unexpected error: {err}");
+
+        assert_eq!(apply(&document, &parse(&json!([
+            {"op": "remove", "path": "/tags"},
+            {"op": "test", "path": "/name", "value": "jq"}
+        ]))?)?, Outcome::TestFailed {index: 1, path: String::from("/name"), expected: json!("jq"), actual: Some(json!("dx"))});
+
+        assert_eq!(apply(&document, &parse(&json!([
+            {"op": "test", "path": "/version", "value": 1.0},
+            {"op": "copy", "from": "/name", "path": "/tags/0"},
+            {"op": "replace", "path": "/version", "value": 2}
+        ]))?)?, Outcome::Patched(json!({"name": "dx", "tags": ["dx", "cli"], "version": 2})));
+        Ok(())
+    }
+
+    #[test]
+    fn compare_numbers_by_value() -> Result<()> {
+        let document = json!({"id": 9007199254740993u64, "version": 1});
+        let outcome = apply(&document, &parse(&json!([{"op": "test", "path": "/version", "value": 1.0}]))?)?;
+        assert_eq!(outcome, Outcome::Patched(document.clone()));
+
+        // Both round to the same float, but are different integers
+        let outcome = apply(&document, &parse(&json!([{"op": "test", "path": "/id", "value": 9007199254740992u64}]))?)?;
+        assert!(matches!(outcome, Outcome::TestFailed {index: 0, ..}), "Unexpected outcome: {outcome:?}");
+        Ok(())
+    }
+}
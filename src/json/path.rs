@@ -0,0 +1,67 @@
+//! Selecting values with RFC 9535 JSONPath expressions such as `$.store.book[?@.price < 10].title`
+
+use jsonpath_rust::parser::errors::JsonPathError;
+use jsonpath_rust::parser::model::{Segment, Selector};
+use jsonpath_rust::parser::parse_json_path;
+use jsonpath_rust::JsonPath;
+use miette::SourceSpan;
+use serde::Serialize;
+use serde_json::Value;
+use crate::json::{pointer, JSONParseError};
+use crate::{Error, Result};
+
+/// A value matched by a JSONPath expression, together with its location as a JSON Pointer
+#[derive(Debug, Serialize)]
+pub struct Match<'a> {
+    pub pointer: String,
+    pub value: &'a Value
+}
+
+/// Returns all nodes selected by the expression in document order
+pub fn select<'a>(value: &'a Value, path: &str) -> Result<Vec<Match<'a>>> {
+    let results = value.query_with_path(path).map_err(|err| syntax_error(path, err))?;
+
+    results.into_iter()
+        .map(|result| {
+            let pointer = to_pointer(&result.clone().path())?;
+            Ok(Match {pointer, value: result.val()})
+        })
+        .collect()
+}
+
+/// Converts a normalized path such as `$['store']['book'][0]` to a JSON Pointer
+fn to_pointer(normalized_path: &str) -> Result<String> {
+    let query = parse_json_path(normalized_path)
+        .map_err(|err| Error::InvalidInput(format!("Failed to convert '{normalized_path}' to a JSON Pointer! {err}")))?;
+
+    let mut pointer = String::new();
+    for segment in query.segments {
+        match segment {
+            Segment::Selector(Selector::Name(name)) => pointer.push_str(&format!("/{}", pointer::escape(&name))),
+            Segment::Selector(Selector::Index(index)) => pointer.push_str(&format!("/{index}")),
+            segment => return Err(Error::InvalidInput(format!("Unexpected segment {segment:?} in the normalized path '{normalized_path}'!")))
+        }
+    }
+    Ok(pointer)
+}
+
+fn syntax_error(path: &str, err: JsonPathError) -> Error {
+    let offset = match &err {
+        JsonPathError::PestError(err) => match err.location {
+            pest::error::InputLocation::Pos(pos) => pos,
+            pest::error::InputLocation::Span((start, _)) => start
+        },
+        _ => 0
+    };
+    let message = match &err {
+        JsonPathError::PestError(err) => err.variant.message().to_string(),
+        err => err.to_string()
+    };
+
+    JSONParseError::new(
+        "JSONPath",
+        path.to_string(),
+        SourceSpan::new(offset.min(path.len()).into(), 1),
+        format!("Failed to parse JSONPath: {message}")
+    ).into()
+}
@@ -0,0 +1,199 @@
+//! Reading and modifying JSON documents at an RFC 6901 JSON Pointer such as `/spec/containers/0/image`
+
+use serde_json::{Map, Value};
+use crate::{Error, Result};
+
+/// Splits the pointer into its unescaped reference tokens
+pub fn parse(pointer: &str) -> Result<Vec<String>> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(tokens) = pointer.strip_prefix('/') else {
+        return Err(Error::InvalidInput(format!("JSON Pointer '{pointer}' is invalid! It must be empty or start with '/'")));
+    };
+
+    tokens.split('/')
+        .map(|token| {
+            let mut unescaped = String::with_capacity(token.len());
+            let mut chars = token.chars();
+            while let Some(char) = chars.next() {
+                if char != '~' {
+                    unescaped.push(char);
+                    continue;
+                }
+                match chars.next() {
+                    Some('0') => unescaped.push('~'),
+                    Some('1') => unescaped.push('/'),
+                    _ => return Err(Error::InvalidInput(format!("JSON Pointer '{pointer}' is invalid! '~' must be followed by '0' or '1'")))
+                }
+            }
+            Ok(unescaped)
+        })
+        .collect()
+}
+
+/// Escapes an object key for use as a reference token
+pub fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Returns the value at the pointer, or `None` if nothing is there
+pub fn get<'a>(value: &'a Value, pointer: &str) -> Result<Option<&'a Value>> {
+    let mut current = value;
+    for token in parse(pointer)? {
+        current = match child(current, &token) {
+            Some(child) => child,
+            None => return Ok(None)
+        };
+    }
+    Ok(Some(current))
+}
+
+/// Returns a mutable reference to the value at the pointer, or `None` if nothing is there
+pub fn get_mut<'a>(value: &'a mut Value, pointer: &str) -> Result<Option<&'a mut Value>> {
+    let mut current = value;
+    for token in parse(pointer)? {
+        current = match child_mut(current, &token) {
+            Some(child) => child,
+            None => return Ok(None)
+        };
+    }
+    Ok(Some(current))
+}
+
+/// Adds the value the way RFC 6902 `add` does: the parent must exist, an array item is inserted before the index
+/// (or appended with `-`) and an existing object member is replaced.
+pub fn insert(value: &mut Value, pointer: &str, new_value: Value) -> Result<()> {
+    let mut tokens = parse(pointer)?;
+    let Some(last) = tokens.pop() else {
+        *value = new_value;
+        return Ok(());
+    };
+
+    let mut current = value;
+    for token in &tokens {
+        current = child_mut(current, token).ok_or_else(|| not_found(pointer))?;
+    }
+    match current {
+        Value::Object(map) => {
+            map.insert(last, new_value);
+        },
+        Value::Array(items) => {
+            let index = array_index(&last, items.len(), pointer)?;
+            if index > items.len() {
+                return Err(out_of_bounds(pointer, index));
+            }
+            items.insert(index, new_value);
+        },
+        _ => return Err(not_a_container(pointer, &last))
+    }
+    Ok(())
+}
+
+/// Sets the value at the pointer. Missing parent objects are created, an existing value (or array item) is replaced
+/// and `-` or the length of an array appends to it.
+pub fn set(value: &mut Value, pointer: &str, new_value: Value) -> Result<()> {
+    let mut tokens = parse(pointer)?;
+    let Some(last) = tokens.pop() else {
+        *value = new_value;
+        return Ok(());
+    };
+
+    let mut current = value;
+    for token in &tokens {
+        if current.is_null() {
+            *current = Value::Object(Map::new());
+        }
+        current = match current {
+            Value::Object(map) => map.entry(token.as_str()).or_insert(Value::Null),
+            Value::Array(items) => {
+                let index = array_index(token, items.len(), pointer)?;
+                items.get_mut(index).ok_or_else(|| out_of_bounds(pointer, index))?
+            },
+            _ => return Err(not_a_container(pointer, token))
+        };
+    }
+
+    if current.is_null() {
+        *current = Value::Object(Map::new());
+    }
+    match current {
+        Value::Object(map) => {
+            map.insert(last, new_value);
+        },
+        Value::Array(items) => {
+            let index = if last == "-" {items.len()} else {array_index(&last, items.len(), pointer)?};
+            match index.cmp(&items.len()) {
+                std::cmp::Ordering::Less => items[index] = new_value,
+                std::cmp::Ordering::Equal => items.push(new_value),
+                std::cmp::Ordering::Greater => return Err(out_of_bounds(pointer, index))
+            }
+        },
+        _ => return Err(not_a_container(pointer, &last))
+    }
+    Ok(())
+}
+
+/// Removes and returns the value at the pointer
+pub fn delete(value: &mut Value, pointer: &str) -> Result<Value> {
+    let mut tokens = parse(pointer)?;
+    let Some(last) = tokens.pop() else {
+        return Err(Error::InvalidInput(String::from("The whole document can't be deleted!")));
+    };
+
+    let mut current = value;
+    for token in &tokens {
+        current = child_mut(current, token).ok_or_else(|| not_found(pointer))?;
+    }
+
+    let removed = match current {
+        Value::Object(map) => map.shift_remove(&last),
+        Value::Array(items) => array_index(&last, items.len(), pointer).ok()
+            .filter(|index| *index < items.len())
+            .map(|index| items.remove(index)),
+        _ => None
+    };
+    removed.ok_or_else(|| not_found(pointer))
+}
+
+fn child<'a>(value: &'a Value, token: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(map) => map.get(token),
+        Value::Array(items) => items.get(array_index(token, items.len(), "").ok()?),
+        _ => None
+    }
+}
+
+fn child_mut<'a>(value: &'a mut Value, token: &str) -> Option<&'a mut Value> {
+    match value {
+        Value::Object(map) => map.get_mut(token),
+        Value::Array(items) => {
+            let index = array_index(token, items.len(), "").ok()?;
+            items.get_mut(index)
+        },
+        _ => None
+    }
+}
+
+/// Array indexes are decimal numbers without leading zeros
+fn array_index(token: &str, len: usize, pointer: &str) -> Result<usize> {
+    if token == "-" {
+        return Ok(len);
+    }
+    let valid = !token.is_empty() && token.chars().all(|char| char.is_ascii_digit()) && (token == "0" || !token.starts_with('0'));
+    token.parse::<usize>().ok()
+        .filter(|_| valid)
+        .ok_or_else(|| Error::InvalidInput(format!("'{token}' in JSON Pointer '{pointer}' is not a valid array index!")))
+}
+
+fn not_found(pointer: &str) -> Error {
+    Error::InvalidInput(format!("Nothing found at JSON Pointer '{pointer}'!"))
+}
+
+fn out_of_bounds(pointer: &str, index: usize) -> Error {
+    Error::InvalidInput(format!("Index {index} in JSON Pointer '{pointer}' is out of bounds!"))
+}
+
+fn not_a_container(pointer: &str, token: &str) -> Error {
+    Error::InvalidInput(format!("Can't set '{token}' of JSON Pointer '{pointer}' because the parent is neither an object nor an array!"))
+}
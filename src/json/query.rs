@@ -0,0 +1,113 @@
+//! jq-style queries such as `.items[] | select(.price > 10) | {name, price}`
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use jaq_core::load::{self, Arena, File, Loader};
+use jaq_core::{Compiler, Ctx, Filter, Native, RcIter};
+use jaq_json::Val;
+use miette::{Diagnostic, NamedSource, SourceSpan};
+use serde_json::Value;
+use crate::Result;
+
+/// A compiled jq filter which can be run against any number of values
+pub struct Query {
+    filter: Filter<Native<Val>>
+}
+
+impl Query {
+    /// Parses the query. Syntax errors and undefined filters are reported with their position in the query.
+    pub fn compile(query: &str) -> Result<Query> {
+        let loader = Loader::new(jaq_std::defs().chain(jaq_json::defs()));
+        let arena = Arena::default();
+        let modules = loader.load(&arena, File {code: query, path: ()})
+            .map_err(|errors| {
+                let (found, message) = match errors.into_iter().next().map(|(_, err)| err) {
+                    Some(load::Error::Lex(errors)) => errors.first().map(|(expected, found)| (*found, format!("expected {}", expected.as_str()))),
+                    Some(load::Error::Parse(errors)) => errors.first().map(|(expected, found)| (*found, format!("expected {}", expected.as_str()))),
+                    _ => None
+                }.unwrap_or(("", String::from("invalid query")));
+                Self::syntax_error(query, found, message)
+            })?;
+
+        let filter = Compiler::default()
+            .with_funs(jaq_std::funs().chain(jaq_json::funs()))
+            .compile(modules)
+            .map_err(|errors| {
+                let (found, message) = errors.into_iter()
+                    .flat_map(|(_, errors)| errors)
+                    .next()
+                    .map(|(name, kind)| (name, format!("undefined {} '{name}'", kind.as_str())))
+                    .unwrap_or(("", String::from("invalid query")));
+                Self::syntax_error(query, found, message)
+            })?;
+
+        Ok(Query {filter})
+    }
+
+    /// Runs the query, returning every value it produces
+    pub fn run(&self, value: &Value) -> Result<Vec<Value>> {
+        let inputs = RcIter::new(core::iter::empty());
+        self.filter.run((Ctx::new([], &inputs), Val::from(value.clone())))
+            .map(|result| result
+                .map(Value::from)
+                .map_err(|err| crate::Error::InvalidInput(format!("The query failed! {err}"))))
+            .collect()
+    }
+
+    /// `found` is a slice of `query`, which gives the offset of the error
+    fn syntax_error(query: &str, found: &str, message: String) -> crate::Error {
+        let mut offset = (found.as_ptr() as usize).saturating_sub(query.as_ptr() as usize).min(query.len());
+        let mut length = found.len();
+        // A label after the last character isn't drawn, e.g. for a query which ends too early
+        if offset == query.len() && let Some((last, char)) = query.char_indices().next_back() {
+            offset = last;
+            length = char.len_utf8();
+        }
+        QuerySyntaxError {
+            src: NamedSource::new("query", query.to_string()),
+            span: SourceSpan::new(offset.into(), length),
+            message: format!("Invalid query: {message}")
+        }.into()
+    }
+}
+
+/// A jq query that couldn't be compiled, underlined in the query
+#[derive(Debug, Diagnostic)]
+pub struct QuerySyntaxError {
+    #[source_code]
+    src: NamedSource<String>,
+
+    #[label("error occurred here")]
+    span: SourceSpan,
+
+    message: String
+}
+
+impl Display for QuerySyntaxError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for QuerySyntaxError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::Error;
+    use super::*;
+    use pretty_assertions::{assert_eq};
+
+    #[test]
+    fn report_invalid_query() {
+        let Err(Error::Query(err)) = Query::compile(".items[] | frobnicate") else {
+            panic!("The filter doesn't exist");
+        };
+        assert_eq!(err.to_string(), "Invalid query: undefined filter 'frobnicate'");
+        assert_eq!(err.span, SourceSpan::new(11.into(), 10));
+
+        let Err(Error::Query(err)) = Query::compile(".a |") else {
+            panic!("The query ends too early");
+        };
+        assert_eq!(err.span, SourceSpan::new(3.into(), 1));
+    }
+}
@@ -0,0 +1,208 @@
+//! Validating JSON documents against JSON Schema drafts 7 and 2020-12, and inferring schemas from samples.
+//! Only local `$ref`s are resolved.
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use jsonschema::Draft;
+use miette::{Diagnostic, LabeledSpan, NamedSource, SourceSpan};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use crate::json::infer::{self, Kind, Shape};
+use crate::json::source;
+use crate::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SchemaDraft {
+    Draft7,
+    Draft202012
+}
+
+/// A single place where the document doesn't match the schema
+#[derive(Debug, Serialize)]
+pub struct Violation {
+    /// JSON Pointer to the offending value in the document
+    pub instance_path: String,
+    /// JSON Pointer to the failed keyword in the schema
+    pub schema_path: String,
+    pub message: String
+}
+
+/// Validates the document. The draft is taken from `$schema` unless given, defaulting to 2020-12.
+/// Returns every violation, an empty list means the document is valid.
+pub fn validate(schema: &Value, instance: &Value, draft: Option<SchemaDraft>) -> Result<Vec<Violation>> {
+    Ok(Validator::new(schema, draft)?.validate(instance))
+}
+
+/// A compiled schema for validating many documents, e.g. every record of a stream
+pub struct Validator {
+    validator: jsonschema::Validator
+}
+
+impl Validator {
+    /// Compiles the schema. The draft is taken from `$schema` unless given, defaulting to 2020-12.
+    pub fn new(schema: &Value, draft: Option<SchemaDraft>) -> Result<Validator> {
+        let mut options = jsonschema::options();
+        if let Some(draft) = draft {
+            options = options.with_draft(match draft {
+                SchemaDraft::Draft7 => Draft::Draft7,
+                SchemaDraft::Draft202012 => Draft::Draft202012
+            });
+        }
+
+        let validator = options.build(schema)
+            .map_err(|err| crate::Error::InvalidInput(format!("The schema is invalid! {err}")))?;
+        Ok(Validator {validator})
+    }
+
+    /// Returns every violation, an empty list means the document is valid
+    pub fn validate(&self, instance: &Value) -> Vec<Violation> {
+        self.validator.iter_errors(instance)
+            .map(|err| Violation {
+                instance_path: err.instance_path().to_string(),
+                schema_path: err.schema_path().to_string(),
+                message: err.to_string()
+            })
+            .collect()
+    }
+}
+
+/// Builds a draft 2020-12 schema that every sample matches. Keys present in all objects are required, and strings that
+/// are all dates, times, UUIDs or email addresses get a `format`.
+pub fn infer<'a>(samples: impl IntoIterator<Item = &'a Value>) -> Value {
+    let mut schema = Map::new();
+    schema.insert(String::from("$schema"), Value::from("https://json-schema.org/draft/2020-12/schema"));
+    if let Value::Object(inferred) = shape_schema(&infer::infer_all(samples)) {
+        schema.extend(inferred);
+    }
+    Value::Object(schema)
+}
+
+fn shape_schema(shape: &Shape) -> Value {
+    let mut schema = Map::new();
+    let name = match &shape.kind {
+        None if shape.nullable => "null",
+        // Only empty arrays were seen, or values of different types, so anything goes
+        None | Some(Kind::Mixed) => return json!({}),
+        Some(Kind::Bool) => "boolean",
+        Some(Kind::Integer(_)) => "integer",
+        Some(Kind::Number) => "number",
+        Some(Kind::String(strings)) => {
+            if let Some(format) = strings.format {
+                schema.insert(String::from("format"), Value::from(format.name()));
+            }
+            "string"
+        },
+        Some(Kind::Array(items)) => {
+            if items.kind.is_some() || items.nullable {
+                schema.insert(String::from("items"), shape_schema(items));
+            }
+            "array"
+        },
+        Some(Kind::Object(object)) => {
+            let properties = object.fields.iter()
+                .map(|field| (field.name.clone(), shape_schema(&field.shape)))
+                .collect::<Map<String, Value>>();
+            let required = object.fields.iter()
+                .filter(|field| !object.is_optional(field))
+                .map(|field| Value::from(field.name.as_str()))
+                .collect::<Vec<Value>>();
+            schema.insert(String::from("properties"), Value::Object(properties));
+            if !required.is_empty() {
+                schema.insert(String::from("required"), Value::Array(required));
+            }
+            "object"
+        }
+    };
+    let ty = match shape.nullable && shape.kind.is_some() {
+        true => json!([name, "null"]),
+        false => Value::from(name)
+    };
+    // `type` comes first to make the schema easier to read
+    let mut typed = Map::new();
+    typed.insert(String::from("type"), ty);
+    typed.extend(schema);
+    Value::Object(typed)
+}
+
+/// All violations of a document, underlined in its source
+#[derive(Debug, Diagnostic)]
+pub struct SchemaValidationError {
+    #[source_code]
+    src: NamedSource<String>,
+
+    #[label(collection)]
+    labels: Vec<LabeledSpan>,
+
+    message: String
+}
+
+impl SchemaValidationError {
+    pub fn new(src_name: &str, src: String, violations: &[Violation]) -> Self {
+        let labels = violations.iter()
+            .map(|violation| {
+                let path = if violation.instance_path.is_empty() {"/"} else {&violation.instance_path};
+                let span = source::span(&src, &violation.instance_path).unwrap_or(SourceSpan::new(0.into(), 0));
+                LabeledSpan::new_with_span(Some(format!("{path}: {}", violation.message)), span)
+            })
+            .collect();
+
+        SchemaValidationError {
+            src: NamedSource::new(src_name, src),
+            labels,
+            message: format!("The JSON doesn't match the schema! Found {} violation(s)", violations.len())
+        }
+    }
+}
+
+impl Display for SchemaValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for SchemaValidationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::{assert_eq};
+
+    #[test]
+    fn report_every_violation() -> Result<()> {
+        let schema = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "properties": {"name": {"type": "string"}, "age": {"$ref": "#/definitions/age"}},
+            "definitions": {"age": {"type": "integer", "minimum": 0}}
+        });
+        let instance = json!({"name": 5, "age": -1});
+        let violations = validate(&schema, &instance, None)?;
+
+        let paths = violations.iter().map(|violation| violation.instance_path.as_str()).collect::<Vec<&str>>();
+        assert_eq!(paths, vec!["/name", "/age"]);
+        let err = SchemaValidationError::new("content", instance.to_string(), &violations);
+        assert_eq!(err.to_string(), "The JSON doesn't match the schema! Found 2 violation(s)");
+        assert_eq!(err.labels.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn infer_from_samples() {
+        let samples = [
+            json!({"id": "6f1c3a4e-2b7d-4c8e-9a0b-1c2d3e4f5a6b", "email": "ann@example.com", "tags": ["a"], "age": 30}),
+            json!({"id": "0b9e7c4d-3f2a-4e1b-8c5d-6a7b8c9d0e1f", "email": "bob@example.com", "tags": [], "age": null, "seen": "2024-05-01T10:00:00Z"})
+        ];
+
+        assert_eq!(infer(&samples), json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": {
+                "id": {"type": "string", "format": "uuid"},
+                "email": {"type": "string", "format": "email"},
+                "tags": {"type": "array", "items": {"type": "string"}},
+                "age": {"type": ["integer", "null"]},
+                "seen": {"type": "string", "format": "date-time"}
+            },
+            "required": ["id", "email", "tags", "age"]
+        }));
+    }
+}
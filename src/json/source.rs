@@ -0,0 +1,133 @@
+//! Mapping JSON Pointers back to their position in the source text, for diagnostics
+
+use miette::SourceSpan;
+use crate::json::pointer;
+
+/// Byte span of the value at the pointer in the source, or `None` if the source doesn't contain it
+pub fn span(src: &str, pointer: &str) -> Option<SourceSpan> {
+    let tokens = pointer::parse(pointer).ok()?;
+    let mut scanner = Scanner {bytes: src.as_bytes(), pos: 0};
+    let (start, end) = scanner.find(&tokens)?;
+    Some(SourceSpan::new(start.into(), end - start))
+}
+
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    pos: usize
+}
+
+impl Scanner<'_> {
+    fn find(&mut self, tokens: &[String]) -> Option<(usize, usize)> {
+        self.skip_whitespace();
+        let Some((token, rest)) = tokens.split_first() else {
+            let start = self.pos;
+            self.skip_value()?;
+            return Some((start, self.pos));
+        };
+
+        match self.peek()? {
+            b'{' => {
+                self.pos += 1;
+                loop {
+                    self.skip_whitespace();
+                    if self.peek()? == b'}' {
+                        return None;
+                    }
+                    let key = self.string()?;
+                    self.skip_whitespace();
+                    self.expect(b':')?;
+                    if key == *token {
+                        return self.find(rest);
+                    }
+                    self.skip_whitespace();
+                    self.skip_value()?;
+                    self.skip_whitespace();
+                    self.expect(b',')?;
+                }
+            },
+            b'[' => {
+                let index = token.parse::<usize>().ok()?;
+                self.pos += 1;
+                for _ in 0..index {
+                    self.skip_whitespace();
+                    self.skip_value()?;
+                    self.skip_whitespace();
+                    self.expect(b',')?;
+                }
+                self.find(rest)
+            },
+            _ => None
+        }
+    }
+
+    fn skip_value(&mut self) -> Option<()> {
+        match self.peek()? {
+            b'"' => self.string().map(|_| ()),
+            open @ (b'{' | b'[') => {
+                let close = if open == b'{' {b'}'} else {b']'};
+                self.pos += 1;
+                loop {
+                    self.skip_whitespace();
+                    match self.peek()? {
+                        byte if byte == close => {
+                            self.pos += 1;
+                            return Some(());
+                        },
+                        b',' | b':' => self.pos += 1,
+                        _ => self.skip_value()?
+                    }
+                }
+            },
+            _ => {
+                // Numbers, true, false and null
+                let len = self.bytes[self.pos..].iter()
+                    .position(|byte| matches!(byte, b',' | b'}' | b']' | b':') || byte.is_ascii_whitespace())
+                    .unwrap_or(self.bytes.len() - self.pos);
+                self.pos += len;
+                (len > 0).then_some(())
+            }
+        }
+    }
+
+    /// Reads a string literal and returns its unescaped value
+    fn string(&mut self) -> Option<String> {
+        let start = self.pos;
+        if self.peek()? != b'"' {
+            return None;
+        }
+        self.pos += 1;
+        let mut escaped = false;
+        loop {
+            let byte = self.peek()?;
+            self.pos += 1;
+            match byte {
+                b'\\' => {
+                    escaped = true;
+                    self.pos += 1;
+                },
+                b'"' => break,
+                _ => {}
+            }
+        }
+
+        let raw = std::str::from_utf8(&self.bytes[start..self.pos]).ok()?;
+        if escaped {
+            return serde_json::from_str(raw).ok();
+        }
+        Some(raw[1..raw.len() - 1].to_string())
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        (self.peek()? == byte).then(|| self.pos += 1)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|byte| byte.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Reading NDJSON and huge top-level arrays one value at a time, so memory use doesn't grow with the input
+
+use std::fmt::Formatter;
+use std::io::{self, BufRead, Read};
+use serde::Deserializer as _;
+use serde::de::{self, SeqAccess, Visitor};
+use serde_json::{Deserializer, Value};
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Layout {
+    /// A single top-level array, read item by item
+    Array,
+    /// Values separated by whitespace, e.g. NDJSON with one value per line
+    Values
+}
+
+/// Detects the layout from the first non-whitespace byte without consuming it. Input starting with `[` is a top-level array.
+pub fn layout(reader: &mut impl BufRead) -> Result<Layout> {
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(Layout::Values);
+        }
+        match buffer.iter().position(|byte| !byte.is_ascii_whitespace()) {
+            Some(index) => return Ok(if buffer[index] == b'[' {Layout::Array} else {Layout::Values}),
+            None => {
+                let length = buffer.len();
+                reader.consume(length);
+            }
+        }
+    }
+}
+
+/// Calls `f` with every value, or every item of the array for [`Layout::Array`], and returns how many there were.
+/// Stops at the first syntax error or the first error returned by `f`.
+pub fn for_each(reader: impl Read, layout: Layout, mut f: impl FnMut(Value) -> Result<()>) -> Result<usize> {
+    match layout {
+        Layout::Values => {
+            let mut count = 0;
+            for value in Deserializer::from_reader(reader).into_iter::<Value>() {
+                f(value.map_err(syntax_error)?)?;
+                count += 1;
+            }
+            Ok(count)
+        },
+        Layout::Array => {
+            let mut deserializer = Deserializer::from_reader(reader);
+            let mut items = Items {f: &mut f, count: 0, error: None};
+            let result = deserializer.deserialize_seq(&mut items);
+            // The error of the callback is reported instead of the one used to stop the deserializer
+            if let Some(err) = items.error {
+                return Err(err);
+            }
+            result.map_err(syntax_error)?;
+            deserializer.end().map_err(syntax_error)?;
+            Ok(items.count)
+        }
+    }
+}
+
+/// Hands the items of an array to the callback as soon as each one is parsed
+struct Items<'a, F> {
+    f: &'a mut F,
+    count: usize,
+    error: Option<Error>
+}
+
+impl<'de, F: FnMut(Value) -> Result<()>> Visitor<'de> for &mut Items<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("an array")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        while let Some(item) = seq.next_element::<Value>()? {
+            if let Err(err) = (self.f)(item) {
+                self.error = Some(err);
+                return Err(de::Error::custom("stopped by the callback"));
+            }
+            self.count += 1;
+        }
+        Ok(())
+    }
+}
+
+fn syntax_error(err: serde_json::Error) -> Error {
+    if err.is_io() {
+        return Error::Io(io::Error::from(err));
+    }
+    Error::InvalidInput(format!("Failed to parse JSON: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use serde_json::json;
+    use super::*;
+    use pretty_assertions::{assert_eq};
+
+    fn collect(input: &str, layout: Layout) -> Result<Vec<Value>> {
+        let mut values = Vec::new();
+        for_each(input.as_bytes(), layout, |value| {
+            values.push(value);
+            Ok(())
+        })?;
+        Ok(values)
+    }
+
+    #[test]
+    fn detect_layout() -> Result<()> {
+        let mut reader = Cursor::new(" \n [1, 2]");
+        assert_eq!(layout(&mut reader)?, Layout::Array);
+        assert_eq!(reader.fill_buf()?.trim_ascii_start(), b"[1, 2]");
+
+        assert_eq!(layout(&mut Cursor::new("{\"a\": [1]}\n[2]"))?, Layout::Values);
+        assert_eq!(layout(&mut Cursor::new(""))?, Layout::Values);
+        Ok(())
+    }
+
+    #[test]
+    fn read_one_value_at_a_time() -> Result<()> {
+        assert_eq!(collect("{\"id\": 1}\n\n{\"id\": \"2\"}\n", Layout::Values)?, vec![json!({"id": 1}), json!({"id": "2"})]);
+        assert_eq!(collect("[1, 2]\n[3]\n", Layout::Values)?, vec![json!([1, 2]), json!([3])]);
+        assert_eq!(collect(" [1, {\"a\": []}] ", Layout::Array)?, vec![json!(1), json!({"a": []})]);
+
+        let err = collect("[1, 2,]", Layout::Array).expect_err("The array has a trailing comma");
+        assert!(err.to_string().contains("trailing comma at line 1 column 7"), "Unexpected error: {err}");
+
+        let mut count = 0;
+        let err = for_each("[1, 2, 3]".as_bytes(), Layout::Array, |_| {
+            count += 1;
+            Err(Error::InvalidInput(String::from("stop")))
+        }).expect_err("The callback fails");
+        assert_eq!((count, err.to_string()), (1, String::from("stop")));
+        Ok(())
+    }
+}
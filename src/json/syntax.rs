@@ -0,0 +1,542 @@
+//! Tolerant syntax checking which reports every error in a JSON document instead of stopping at the first one
+
+use std::ops::Range;
+
+/// Deepest nesting of objects and arrays that is checked, the most serde_json accepts
+pub const MAX_DEPTH: usize = 127;
+
+/// A syntax error with the byte range it covers. Zero-length ranges point between two characters, e.g. at a missing comma.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub span: Range<usize>,
+    pub message: String,
+    pub hint: Option<Hint>
+}
+
+/// Common mistakes, most of them valid JSON5
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Hint {
+    Comment,
+    TrailingComma,
+    SingleQuotes,
+    UnquotedKey,
+    MissingComma
+}
+
+impl Hint {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Hint::Comment => "JSON doesn't support comments",
+            Hint::TrailingComma => "JSON doesn't allow a comma after the last item",
+            Hint::SingleQuotes => "JSON strings must use double quotes",
+            Hint::UnquotedKey => "Object keys must be double-quoted strings",
+            Hint::MissingComma => "Items of objects and arrays must be separated by commas"
+        }
+    }
+
+    /// Whether JSON5 accepts the construct
+    pub fn is_json5(&self) -> bool {
+        !matches!(self, Hint::MissingComma)
+    }
+}
+
+/// Checks the whole document, recovering after every error. Returns no errors for valid JSON.
+pub fn check(src: &str) -> Vec<SyntaxError> {
+    let mut errors = Vec::new();
+    let tokens = tokenize(src, &mut errors);
+    let mut parser = Parser {tokens: &tokens, position: 0, end: src.len(), depth: 0, stopped: false, errors};
+
+    parser.value();
+    if let Some(token) = parser.peek() {
+        let span = token.span.start..tokens.last().map_or(src.len(), |token| token.span.end);
+        parser.error(span, "unexpected content after the document", None);
+    }
+
+    let mut errors = parser.errors;
+    // Labels after the last character aren't drawn, so errors at the end of the input point at the last character
+    if let Some((last, _)) = src.char_indices().next_back() {
+        for error in errors.iter_mut().filter(|error| error.span.start >= src.len()) {
+            error.span = last..src.len();
+        }
+    }
+    errors.sort_by_key(|error| error.span.start);
+    errors
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    OpenBrace,
+    CloseBrace,
+    OpenBracket,
+    CloseBracket,
+    Colon,
+    Comma,
+    String,
+    /// Numbers, `true`, `false` and `null`
+    Scalar,
+    /// A single quoted string, already reported. Treated as a string
+    SingleQuoted,
+    /// Unquoted word other than a literal, e.g. an unquoted key
+    Identifier,
+    Invalid
+}
+
+#[derive(Debug)]
+struct Token {
+    kind: Kind,
+    span: Range<usize>
+}
+
+fn tokenize(src: &str, errors: &mut Vec<SyntaxError>) -> Vec<Token> {
+    let bytes = src.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let kind = match bytes[i] {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                i += 1;
+                continue;
+            },
+            b'{' => Kind::OpenBrace,
+            b'}' => Kind::CloseBrace,
+            b'[' => Kind::OpenBracket,
+            b']' => Kind::CloseBracket,
+            b':' => Kind::Colon,
+            b',' => Kind::Comma,
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = src[i..].find('\n').map_or(bytes.len(), |end| i + end);
+                let end = if src[..i].ends_with('\r') {i - 1} else {i};
+                errors.push(error(start..end, "comment", Some(Hint::Comment)));
+                continue;
+            },
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                match src[i + 2..].find("*/") {
+                    Some(end) => {
+                        i += end + 4;
+                        errors.push(error(start..i, "comment", Some(Hint::Comment)));
+                    },
+                    None => {
+                        i = bytes.len();
+                        errors.push(error(start..i, "unterminated comment", Some(Hint::Comment)));
+                    }
+                }
+                continue;
+            },
+            quote @ (b'"' | b'\'') => {
+                i = string(src, i, quote, errors);
+                if quote == b'\'' {
+                    errors.push(error(start..i, "single-quoted string", Some(Hint::SingleQuotes)));
+                    tokens.push(Token {kind: Kind::SingleQuoted, span: start..i});
+                } else {
+                    tokens.push(Token {kind: Kind::String, span: start..i});
+                }
+                continue;
+            },
+            b'-' | b'+' | b'.' | b'0'..=b'9' => {
+                i = word_end(src, i);
+                if !is_number(&src[start..i]) {
+                    errors.push(error(start..i, format!("invalid number '{}'", &src[start..i]), None));
+                }
+                tokens.push(Token {kind: Kind::Scalar, span: start..i});
+                continue;
+            },
+            byte if byte.is_ascii_alphabetic() || byte == b'_' || byte == b'$' => {
+                i = word_end(src, i);
+                let kind = match &src[start..i] {
+                    "true" | "false" | "null" => Kind::Scalar,
+                    _ => Kind::Identifier
+                };
+                tokens.push(Token {kind, span: start..i});
+                continue;
+            },
+            _ => {
+                let char = src[i..].chars().next().unwrap_or_default();
+                i += char.len_utf8();
+                errors.push(error(start..i, format!("unexpected character '{}'", char.escape_debug()), None));
+                tokens.push(Token {kind: Kind::Invalid, span: start..i});
+                continue;
+            }
+        };
+        i += 1;
+        tokens.push(Token {kind, span: start..i});
+    }
+    tokens
+}
+
+/// Returns the end of the string starting at `start`. Unterminated strings end at the line break.
+fn string(src: &str, start: usize, quote: u8, errors: &mut Vec<SyntaxError>) -> usize {
+    let bytes = src.as_bytes();
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            byte if byte == quote => return i + 1,
+            b'\n' | b'\r' => break,
+            b'\\' => {
+                let escape = src[i + 1..].chars().next();
+                let valid = match escape {
+                    Some('"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't') => true,
+                    Some('u') => src.get(i + 2..i + 6).is_some_and(|hex| hex.chars().all(|char| char.is_ascii_hexdigit())),
+                    Some('\'') => quote == b'\'',
+                    _ => false
+                };
+                let end = i + 1 + escape.map_or(0, char::len_utf8);
+                if !valid && escape.is_some_and(|char| char != '\n' && char != '\r') {
+                    errors.push(error(i..end, "invalid escape sequence", None));
+                }
+                i = end;
+            },
+            byte if byte < 0x20 => {
+                errors.push(error(i..i + 1, "control character in string", None));
+                i += 1;
+            },
+            _ => i += 1
+        }
+    }
+    errors.push(error(start..i, "unterminated string", None));
+    i
+}
+
+fn word_end(src: &str, start: usize) -> usize {
+    src[start..].find(|char: char| !(char.is_ascii_alphanumeric() || matches!(char, '_' | '$' | '.' | '+' | '-')))
+        .map_or(src.len(), |end| start + end)
+}
+
+/// `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`
+fn is_number(text: &str) -> bool {
+    let digits = |text: &str| text.len() - text.trim_start_matches(|char: char| char.is_ascii_digit()).len();
+    let rest = text.strip_prefix('-').unwrap_or(text);
+    let integer = digits(rest);
+    if integer == 0 || (integer > 1 && rest.starts_with('0')) {
+        return false;
+    }
+    let mut rest = &rest[integer..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let count = digits(fraction);
+        if count == 0 {
+            return false;
+        }
+        rest = &fraction[count..];
+    }
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        let count = digits(exponent);
+        if count == 0 {
+            return false;
+        }
+        rest = &exponent[count..];
+    }
+    rest.is_empty()
+}
+
+fn error(span: Range<usize>, message: impl Into<String>, hint: Option<Hint>) -> SyntaxError {
+    SyntaxError {span, message: message.into(), hint}
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    end: usize,
+    /// Objects and arrays entered
+    depth: usize,
+    /// Set once the nesting is too deep. The rest of the input is skipped and nothing more is reported.
+    stopped: bool,
+    errors: Vec<SyntaxError>
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn peek_kind(&self) -> Option<Kind> {
+        self.peek().map(|token| token.kind)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    /// End of the previous token, where missing tokens are reported
+    fn previous_end(&self) -> usize {
+        self.position.checked_sub(1).and_then(|index| self.tokens.get(index)).map_or(0, |token| token.span.end)
+    }
+
+    fn error(&mut self, span: Range<usize>, message: impl Into<String>, hint: Option<Hint>) {
+        self.errors.push(error(span, message, hint));
+    }
+
+    fn value(&mut self) {
+        let Some(token) = self.peek() else {
+            let message = if self.end == 0 {"the input is empty, expected a value"} else {"unexpected end of input, expected a value"};
+            self.error(self.end..self.end, message, None);
+            return;
+        };
+        let span = token.span.clone();
+        match token.kind {
+            Kind::OpenBrace | Kind::OpenBracket if self.depth == MAX_DEPTH => {
+                self.error(span, format!("nesting too deep, at most {MAX_DEPTH} levels are supported"), None);
+                self.position = self.tokens.len();
+                self.stopped = true;
+            },
+            Kind::OpenBrace => {
+                self.depth += 1;
+                self.object();
+                self.depth -= 1;
+            },
+            Kind::OpenBracket => {
+                self.depth += 1;
+                self.array();
+                self.depth -= 1;
+            },
+            Kind::String | Kind::Scalar | Kind::SingleQuoted | Kind::Invalid => {
+                self.next();
+            },
+            Kind::Identifier => {
+                self.next();
+                self.error(span, "unexpected word, expected a value (strings must be quoted)", None);
+            },
+            // Left for the enclosing object or array to recover from
+            Kind::CloseBrace | Kind::CloseBracket | Kind::Comma | Kind::Colon => self.error(span, "expected a value", None)
+        }
+    }
+
+    fn object(&mut self) {
+        let open = self.next().map_or(0..0, |token| token.span.clone());
+        if self.peek_kind() == Some(Kind::CloseBrace) {
+            self.next();
+            return;
+        }
+
+        loop {
+            match self.peek_kind() {
+                Some(Kind::String | Kind::SingleQuoted) => {
+                    self.next();
+                },
+                Some(Kind::Identifier | Kind::Scalar) => {
+                    let span = self.next().map_or(0..0, |token| token.span.clone());
+                    self.error(span, "unquoted key", Some(Hint::UnquotedKey));
+                },
+                None => {
+                    if !self.stopped {
+                        self.error(open, "unclosed object", None);
+                    }
+                    return;
+                },
+                Some(_) => {
+                    let span = self.peek().map_or(0..0, |token| token.span.clone());
+                    self.error(span, "expected a key", None);
+                    if self.recover(Kind::CloseBrace) {
+                        return;
+                    }
+                    continue;
+                }
+            }
+
+            match self.peek_kind() {
+                Some(Kind::Colon) => {
+                    self.next();
+                },
+                Some(Kind::OpenBrace | Kind::OpenBracket | Kind::String | Kind::Scalar | Kind::SingleQuoted) => {
+                    let end = self.previous_end();
+                    self.error(end..end, "missing ':' after the key", None);
+                },
+                _ => {
+                    let end = self.previous_end();
+                    self.error(end..end, "expected ':' after the key", None);
+                    if self.recover(Kind::CloseBrace) {
+                        return;
+                    }
+                    continue;
+                }
+            }
+            self.value();
+
+            if self.separator(Kind::CloseBrace, &open, "object") {
+                return;
+            }
+        }
+    }
+
+    fn array(&mut self) {
+        let open = self.next().map_or(0..0, |token| token.span.clone());
+        if self.peek_kind() == Some(Kind::CloseBracket) {
+            self.next();
+            return;
+        }
+
+        loop {
+            self.value();
+            if self.separator(Kind::CloseBracket, &open, "array") {
+                return;
+            }
+        }
+    }
+
+    /// Handles what follows an item. Returns true once the object or array is closed.
+    fn separator(&mut self, close: Kind, open: &Range<usize>, name: &str) -> bool {
+        let closing = if close == Kind::CloseBrace {'}'} else {']'};
+        match self.peek_kind() {
+            Some(Kind::Comma) => {
+                let comma = self.next().map_or(0..0, |token| token.span.clone());
+                if self.peek_kind() == Some(close) {
+                    self.error(comma, "trailing comma", Some(Hint::TrailingComma));
+                    self.next();
+                    return true;
+                }
+                false
+            },
+            Some(kind) if kind == close => {
+                self.next();
+                true
+            },
+            None => {
+                if !self.stopped {
+                    self.error(open.clone(), format!("unclosed {name}"), None);
+                }
+                true
+            },
+            // Another item right after the previous one
+            Some(Kind::String | Kind::Scalar | Kind::SingleQuoted | Kind::Identifier | Kind::OpenBrace | Kind::OpenBracket) => {
+                let end = self.previous_end();
+                self.error(end..end, "missing comma", Some(Hint::MissingComma));
+                false
+            },
+            Some(_) => {
+                let span = self.peek().map_or(0..0, |token| token.span.clone());
+                self.error(span, format!("expected ',' or '{closing}'"), None);
+                self.recover(close)
+            }
+        }
+    }
+
+    /// Skips to the next comma or the closing token of the current object or array. Returns true if it was closed.
+    fn recover(&mut self, close: Kind) -> bool {
+        let mut depth = 0;
+        while let Some(kind) = self.peek_kind() {
+            match kind {
+                Kind::OpenBrace | Kind::OpenBracket => depth += 1,
+                Kind::CloseBrace | Kind::CloseBracket if depth > 0 => depth -= 1,
+                kind if kind == close => {
+                    self.next();
+                    return true;
+                },
+                // A different closing token belongs to an enclosing value
+                Kind::CloseBrace | Kind::CloseBracket => return true,
+                Kind::Comma if depth == 0 => {
+                    self.next();
+                    return false;
+                },
+                _ => {}
+            }
+            self.next();
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use miette::{GraphicalReportHandler, GraphicalTheme};
+    use super::*;
+    use pretty_assertions::{assert_eq};
+
+    fn errors(src: &str) -> Vec<(String, &str)> {
+        check(src).into_iter().map(|error| (error.message, &src[error.span])).collect()
+    }
+
+    #[test]
+    fn recover_after_errors() {
+        assert_eq!(errors(r#"{"a" 1, "b" , "c": 2}"#), vec![
+            (String::from("missing ':' after the key"), ""),
+            (String::from("expected ':' after the key"), "")
+        ]);
+        assert_eq!(errors(r#"{: 1, "a": [1 2, :], "b": nul}"#), vec![
+            (String::from("expected a key"), ":"),
+            (String::from("missing comma"), ""),
+            (String::from("expected a value"), ":"),
+            (String::from("expected ',' or ']'"), ":"),
+            (String::from("unexpected word, expected a value (strings must be quoted)"), "nul")
+        ]);
+    }
+
+    #[test]
+    fn spans_with_crlf_and_multibyte_characters() {
+        assert_eq!(errors("{\r\n  // note\r\n  \"a\": [\"b\r\n]\r\n}"), vec![
+            (String::from("comment"), "// note"),
+            (String::from("unterminated string"), "\"b")
+        ]);
+        assert_eq!(errors("{\"é\": 'ü', \"ñ\": ✓}"), vec![
+            (String::from("single-quoted string"), "'ü'"),
+            (String::from("unexpected character '✓'"), "✓")
+        ]);
+        // The end of the input is clamped to the last character, not its last byte
+        assert_eq!(errors("[1, /* ü"), vec![
+            (String::from("unclosed array"), "["),
+            (String::from("unterminated comment"), "/* ü"),
+            (String::from("unexpected end of input, expected a value"), "ü")
+        ]);
+    }
+
+    #[test]
+    fn report_every_error() {
+        let src = "{\r\n  \"näme\": \"é\" \"x\": 1,\r\n  key: [1, 2,],\r\n}";
+        assert_eq!(errors(src), vec![
+            (String::from("missing comma"), ""),
+            (String::from("unquoted key"), "key"),
+            (String::from("trailing comma"), ","),
+            (String::from("trailing comma"), ",")
+        ]);
+        assert_eq!(check(src).first().map(|error| error.span.start), src.find(" \"x\""));
+    }
+
+    #[test]
+    fn label_errors_at_the_end_of_the_input() {
+        let labels = |src: &str| {
+            let Err(crate::Error::Json(err)) = crate::json::parse(src, "content") else {
+                panic!("The JSON should be invalid!");
+            };
+            let mut report = String::new();
+            GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor()).render_report(&mut report, &*err)
+                .expect("The report should render");
+            report.matches("╰── ").count()
+        };
+        assert_eq!(labels("{\r\n  \"näme\": \"é\" \"x\": 1,\r\n  key: [1, 2,],\r\n}"), 4);
+        assert_eq!(labels("{\"a\":"), 2);
+        assert_eq!(labels("[[[[\n"), 5);
+    }
+
+    #[test]
+    fn suggest_json5_only_when_every_error_is_json5() {
+        let json5 = |src: &str| match crate::json::parse(src, "content") {
+            Err(crate::Error::Json(err)) => err.is_json5(),
+            _ => panic!("The JSON should be invalid!")
+        };
+        assert!(json5("{a: 'b', // comment\n}"));
+        assert!(!json5("{a: 'b' \"c\": 1}"));
+        assert!(!json5("{\"a\" 1,}"));
+    }
+
+    #[test]
+    fn word_the_json5_hint_by_error_count() {
+        let help = |src: &str| match crate::json::parse(src, "content") {
+            Err(crate::Error::Json(err)) => miette::Diagnostic::help(err.as_ref()).map(|help| help.to_string()).unwrap_or_default(),
+            _ => panic!("The JSON should be invalid!")
+        };
+        assert!(help("[1, 2,]").ends_with("JSON5 allows this"), "Unexpected help: {}", help("[1, 2,]"));
+        assert!(help("{a: 'b',}").ends_with("JSON5 allows all of these"), "Unexpected help: {}", help("{a: 'b',}"));
+    }
+
+    #[test]
+    fn stop_at_nesting_too_deep() {
+        let errors = check(&"[".repeat(200_000));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].span, MAX_DEPTH..MAX_DEPTH + 1);
+        assert!(errors[0].message.starts_with("nesting too deep"), "Unexpected error: {}", errors[0].message);
+
+        let deepest = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert_eq!(check(&deepest), Vec::new());
+    }
+}
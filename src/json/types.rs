@@ -0,0 +1,538 @@
+//! Generating Rust, TypeScript, Go and Python type definitions from an inferred shape
+
+use stringcase::{pascal_case, snake_case};
+use crate::json::infer::{Kind, Object, Shape};
+use crate::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+    /// Structs with serde derives
+    Rust,
+    /// Interfaces and string literal unions
+    TypeScript,
+    /// Structs with `json` tags
+    Go,
+    /// Dataclasses and `Literal` aliases
+    Python
+}
+
+/// A language independent type of a field
+#[derive(Debug, Clone, PartialEq)]
+enum Type {
+    Bool,
+    Integer,
+    /// Integers above the signed 64-bit range, never negative
+    Unsigned,
+    Number,
+    String,
+    /// Values of different types, or only nulls
+    Any,
+    Array(Box<Type>),
+    /// A struct or enum defined by the generated code
+    Named(String),
+    Nullable(Box<Type>)
+}
+
+struct Property {
+    key: String,
+    ty: Type,
+    /// Missing in some of the samples
+    optional: bool
+}
+
+enum Definition {
+    Struct {name: String, properties: Vec<Property>},
+    Enum {name: String, values: Vec<String>}
+}
+
+#[derive(Default)]
+struct Collector {
+    definitions: Vec<Definition>
+}
+
+/// Generates the definitions for the shape, the root type first. The shape must be an object, or an array of objects
+/// whose items are then the samples.
+pub fn generate(shape: &Shape, name: &str, language: Language) -> Result<String> {
+    let root = match &shape.kind {
+        Some(Kind::Array(items)) => &items.kind,
+        kind => kind
+    };
+    let Some(Kind::Object(object)) = root else {
+        return Err(Error::InvalidInput(String::from("Types can only be generated for objects or arrays of objects!")));
+    };
+
+    let mut collector = Collector::default();
+    collector.object(object, name);
+    let definitions = collector.definitions;
+    let code = match language {
+        Language::Rust => rust(&definitions),
+        Language::TypeScript => typescript(&definitions),
+        Language::Go => go(&definitions),
+        Language::Python => python(&definitions)
+    };
+    Ok(code)
+}
+
+impl Collector {
+    /// Adds the struct and the types of its fields, returning its name
+    fn object(&mut self, object: &Object, hint: &str) -> String {
+        let name = self.unique(type_name(hint));
+        // Reserve the position so the struct comes before the types of its fields
+        let index = self.definitions.len();
+        self.definitions.push(Definition::Struct {name: name.clone(), properties: Vec::new()});
+        let properties = object.fields.iter()
+            .map(|field| Property {key: field.name.clone(), ty: self.type_of(&field.shape, &field.name), optional: object.is_optional(field)})
+            .collect();
+        self.definitions[index] = Definition::Struct {name: name.clone(), properties};
+        name
+    }
+
+    fn type_of(&mut self, shape: &Shape, hint: &str) -> Type {
+        let ty = match &shape.kind {
+            None | Some(Kind::Mixed) => return Type::Any,
+            Some(Kind::Bool) => Type::Bool,
+            // Only a float holds both negative integers and ones above `i64::MAX`
+            Some(Kind::Integer(integers)) => match (integers.above_i64, integers.negative) {
+                (false, _) => Type::Integer,
+                (true, false) => Type::Unsigned,
+                (true, true) => Type::Number
+            },
+            Some(Kind::Number) => Type::Number,
+            Some(Kind::String(strings)) => match strings.enumeration() {
+                Some(values) => {
+                    let name = self.unique(type_name(hint));
+                    self.definitions.push(Definition::Enum {name: name.clone(), values: values.to_vec()});
+                    Type::Named(name)
+                },
+                None => Type::String
+            },
+            Some(Kind::Array(items)) => Type::Array(Box::new(self.type_of(items, &singular(hint)))),
+            Some(Kind::Object(object)) => Type::Named(self.object(object, hint))
+        };
+        if shape.nullable {Type::Nullable(Box::new(ty))} else {ty}
+    }
+
+    fn unique(&self, name: String) -> String {
+        let taken = |candidate: &str| self.definitions.iter().any(|definition| match definition {
+            Definition::Struct {name, ..} | Definition::Enum {name, ..} => name == candidate
+        });
+        if !taken(&name) {
+            return name;
+        }
+        (2..).map(|number| format!("{name}{number}")).find(|candidate| !taken(candidate)).unwrap_or(name)
+    }
+}
+
+/// Type name for the hint, with `Type` appended when it clashes with a keyword or a built-in type of one of the languages
+fn type_name(hint: &str) -> String {
+    let name = pascal_identifier(hint, "Type");
+    if RESERVED_TYPE_NAMES.contains(&name.as_str()) {format!("{name}Type")} else {name}
+}
+
+const RESERVED_TYPE_NAMES: &[&str] = &[
+    "Self", "None", "True", "False", "String", "Vec", "Option", "Box", "Result", "Value", "Serialize", "Deserialize",
+    "Number", "Boolean", "Object", "Array", "Record", "Date", "Any", "Optional", "Literal"
+];
+
+/// PascalCase name that is a valid identifier in every language, or `fallback` followed by the name when it isn't
+fn pascal_identifier(text: &str, fallback: &str) -> String {
+    let name = pascal_case(text).chars().filter(char::is_ascii_alphanumeric).collect::<String>();
+    match name.chars().next() {
+        None => fallback.to_string(),
+        Some(first) if first.is_ascii_digit() => format!("{fallback}{name}"),
+        _ => name
+    }
+}
+
+/// Name hint for the items of an array, e.g. `Tag` for `tags` and `Address` for `addresses`
+fn singular(hint: &str) -> String {
+    if let Some(stem) = hint.strip_suffix("ies").filter(|stem| !stem.is_empty()) {
+        format!("{stem}y")
+    } else if let Some(stem) = hint.strip_suffix("sses").filter(|stem| !stem.is_empty()) {
+        format!("{stem}ss")
+    } else if hint.ends_with("ss") || hint.ends_with("us") {
+        // Already singular, like `status` or `class`
+        hint.to_string()
+    } else if let Some(stem) = hint.strip_suffix('s').filter(|stem| !stem.is_empty() && !stem.ends_with('s')) {
+        stem.to_string()
+    } else {
+        format!("{hint}Item")
+    }
+}
+
+/// Makes the field names of a struct unique by appending numbers
+fn dedupe(names: Vec<String>) -> Vec<String> {
+    let mut unique = Vec::<String>::with_capacity(names.len());
+    for name in names {
+        let candidate = (1..)
+            .map(|number| if number == 1 {name.clone()} else {format!("{name}{number}")})
+            .find(|candidate| !unique.contains(candidate))
+            .unwrap_or(name);
+        unique.push(candidate);
+    }
+    unique
+}
+
+/// snake_case identifier, or `fallback` followed by the name when it isn't a valid identifier
+fn snake_identifier(key: &str) -> String {
+    let name = snake_case(key).chars().filter(|char| char.is_ascii_alphanumeric() || *char == '_').collect::<String>();
+    match name.chars().next() {
+        None => String::from("field"),
+        Some(first) if first.is_ascii_digit() => format!("field_{name}"),
+        _ => name
+    }
+}
+
+fn quote(text: &str) -> String {
+    serde_json::to_string(text).unwrap_or_else(|_| format!("\"{text}\""))
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false", "fn", "for", "gen",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static", "struct",
+    "trait", "true", "try", "type", "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final",
+    "macro", "override", "priv", "typeof", "unsized", "virtual", "yield"
+];
+
+fn rust(definitions: &[Definition]) -> String {
+    fn render(ty: &Type) -> String {
+        match ty {
+            Type::Bool => String::from("bool"),
+            Type::Integer => String::from("i64"),
+            Type::Unsigned => String::from("u64"),
+            Type::Number => String::from("f64"),
+            Type::String => String::from("String"),
+            Type::Any => String::from("serde_json::Value"),
+            Type::Array(items) => format!("Vec<{}>", render(items)),
+            Type::Named(name) => name.clone(),
+            Type::Nullable(ty) => format!("Option<{}>", render(ty))
+        }
+    }
+
+    let mut blocks = vec![String::from("use serde::{Deserialize, Serialize};")];
+    for definition in definitions {
+        let mut lines = Vec::new();
+        match definition {
+            Definition::Struct {name, properties} => {
+                lines.push(String::from("#[derive(Debug, Clone, Serialize, Deserialize)]"));
+                lines.push(format!("pub struct {name} {{"));
+                let fields = dedupe(properties.iter()
+                    .map(|property| match snake_identifier(&property.key) {
+                        // `self`, `super` and `crate` can't be raw identifiers
+                        field if ["self", "super", "crate"].contains(&field.as_str()) => format!("{field}_"),
+                        field => field
+                    })
+                    .collect());
+                for (property, field) in properties.iter().zip(fields) {
+                    if field != property.key {
+                        lines.push(format!("    #[serde(rename = {:?})]", property.key));
+                    }
+                    let field = if RUST_KEYWORDS.contains(&field.as_str()) {format!("r#{field}")} else {field};
+                    let ty = match &property.ty {
+                        ty @ Type::Nullable(_) => render(ty),
+                        ty if property.optional => format!("Option<{}>", render(ty)),
+                        ty => render(ty)
+                    };
+                    if property.optional {
+                        lines.push(String::from("    #[serde(default, skip_serializing_if = \"Option::is_none\")]"));
+                    }
+                    lines.push(format!("    pub {field}: {ty},"));
+                }
+                lines.push(String::from("}"));
+            },
+            Definition::Enum {name, values} => {
+                lines.push(String::from("#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]"));
+                lines.push(format!("pub enum {name} {{"));
+                let variants = dedupe(values.iter()
+                    .map(|value| match pascal_identifier(value, "Value") {
+                        // `Self` is a keyword
+                        variant if variant == "Self" => format!("{variant}Value"),
+                        variant => variant
+                    })
+                    .collect());
+                for (value, variant) in values.iter().zip(variants) {
+                    if variant != *value {
+                        lines.push(format!("    #[serde(rename = {value:?})]"));
+                    }
+                    lines.push(format!("    {variant},"));
+                }
+                lines.push(String::from("}"));
+            }
+        }
+        blocks.push(lines.join("\n"));
+    }
+    blocks.join("\n\n")
+}
+
+fn typescript(definitions: &[Definition]) -> String {
+    fn render(ty: &Type) -> String {
+        match ty {
+            Type::Bool => String::from("boolean"),
+            Type::Integer | Type::Unsigned | Type::Number => String::from("number"),
+            Type::String => String::from("string"),
+            Type::Any => String::from("unknown"),
+            Type::Array(items) => match items.as_ref() {
+                items @ Type::Nullable(_) => format!("({})[]", render(items)),
+                items => format!("{}[]", render(items))
+            },
+            Type::Named(name) => name.clone(),
+            Type::Nullable(ty) => format!("{} | null", render(ty))
+        }
+    }
+    let is_identifier = |key: &str| {
+        key.chars().next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_' || first == '$')
+            && key.chars().all(|char| char.is_ascii_alphanumeric() || char == '_' || char == '$')
+    };
+
+    let blocks = definitions.iter()
+        .map(|definition| match definition {
+            Definition::Struct {name, properties} => {
+                let mut lines = vec![format!("export interface {name} {{")];
+                for property in properties {
+                    let key = if is_identifier(&property.key) {property.key.clone()} else {quote(&property.key)};
+                    let optional = if property.optional {"?"} else {""};
+                    lines.push(format!("  {key}{optional}: {};", render(&property.ty)));
+                }
+                lines.push(String::from("}"));
+                lines.join("\n")
+            },
+            Definition::Enum {name, values} => {
+                format!("export type {name} = {};", values.iter().map(|value| quote(value)).collect::<Vec<String>>().join(" | "))
+            }
+        })
+        .collect::<Vec<String>>();
+    blocks.join("\n\n")
+}
+
+fn go(definitions: &[Definition]) -> String {
+    fn render(ty: &Type) -> String {
+        match ty {
+            Type::Bool => String::from("bool"),
+            Type::Integer => String::from("int64"),
+            Type::Unsigned => String::from("uint64"),
+            Type::Number => String::from("float64"),
+            Type::String => String::from("string"),
+            Type::Any => String::from("any"),
+            Type::Array(items) => format!("[]{}", render(items)),
+            Type::Named(name) => name.clone(),
+            // Slices can already be nil
+            Type::Nullable(ty) if matches!(ty.as_ref(), Type::Array(_)) => render(ty),
+            Type::Nullable(ty) => format!("*{}", render(ty))
+        }
+    }
+    // Columns are aligned like gofmt does
+    let align = |rows: Vec<Vec<String>>, indent: &str| -> Vec<String> {
+        let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+        let widths = (0..columns)
+            .map(|column| rows.iter().filter_map(|row| row.get(column)).map(String::len).max().unwrap_or_default())
+            .collect::<Vec<usize>>();
+        rows.iter()
+            .map(|row| {
+                let cells = row.iter().enumerate()
+                    .map(|(column, cell)| if column + 1 == row.len() {cell.clone()} else {format!("{cell:<width$}", width = widths[column])})
+                    .collect::<Vec<String>>();
+                format!("{indent}{}", cells.join(" "))
+            })
+            .collect()
+    };
+
+    let blocks = definitions.iter()
+        .map(|definition| match definition {
+            Definition::Struct {name, properties} => {
+                let fields = dedupe(properties.iter().map(|property| pascal_identifier(&property.key, "Field")).collect());
+                let rows = properties.iter().zip(fields)
+                    .map(|(property, field)| {
+                        let ty = match &property.ty {
+                            ty @ (Type::Any | Type::Array(_) | Type::Nullable(_)) => render(ty),
+                            ty if property.optional => format!("*{}", render(ty)),
+                            ty => render(ty)
+                        };
+                        let omit = if property.optional {",omitempty"} else {""};
+                        vec![field, ty, format!("`json:{}`", quote(&format!("{}{omit}", property.key)))]
+                    })
+                    .collect();
+                let mut lines = vec![format!("type {name} struct {{")];
+                lines.extend(align(rows, "\t"));
+                lines.push(String::from("}"));
+                lines.join("\n")
+            },
+            Definition::Enum {name, values} => {
+                let constants = dedupe(values.iter().map(|value| format!("{name}{}", pascal_identifier(value, "Value"))).collect());
+                let rows = values.iter().zip(constants)
+                    .map(|(value, constant)| vec![constant, name.clone(), format!("= {}", quote(value))])
+                    .collect();
+                let mut lines = vec![format!("type {name} string"), String::new(), String::from("const (")];
+                lines.extend(align(rows, "\t"));
+                lines.push(String::from(")"));
+                lines.join("\n")
+            }
+        })
+        .collect::<Vec<String>>();
+    blocks.join("\n\n")
+}
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+    "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal",
+    "not", "or", "pass", "raise", "return", "try", "while", "with", "yield"
+];
+
+fn python(definitions: &[Definition]) -> String {
+    fn render(ty: &Type, typing: &mut Vec<&'static str>) -> String {
+        let mut import = |name: &'static str| if !typing.contains(&name) {typing.push(name)};
+        match ty {
+            Type::Bool => String::from("bool"),
+            Type::Integer | Type::Unsigned => String::from("int"),
+            Type::Number => String::from("float"),
+            Type::String => String::from("str"),
+            Type::Any => {
+                import("Any");
+                String::from("Any")
+            },
+            Type::Array(items) => format!("list[{}]", render(items, typing)),
+            Type::Named(name) => name.clone(),
+            Type::Nullable(ty) => {
+                import("Optional");
+                format!("Optional[{}]", render(ty, typing))
+            }
+        }
+    }
+
+    let mut typing = Vec::new();
+    let mut dataclasses = false;
+    let mut renamed = false;
+    let blocks = definitions.iter()
+        .map(|definition| match definition {
+            Definition::Struct {name, properties} => {
+                dataclasses = true;
+                let fields = dedupe(properties.iter()
+                    .map(|property| match snake_identifier(&property.key) {
+                        // A `field` attribute would hide `dataclasses.field` in the rest of the class
+                        field if PYTHON_KEYWORDS.contains(&field.as_str()) || field == "field" => format!("{field}_"),
+                        field => field
+                    })
+                    .collect());
+                // Fields with a default value have to come after the ones without
+                let mut fields = properties.iter().zip(fields).collect::<Vec<(&Property, String)>>();
+                fields.sort_by_key(|(property, _)| property.optional);
+
+                let mut lines = vec![String::from("@dataclass"), format!("class {name}:")];
+                for (property, field) in fields {
+                    let ty = match &property.ty {
+                        ty @ (Type::Nullable(_) | Type::Any) => render(ty, &mut typing),
+                        ty if property.optional => render(&Type::Nullable(Box::new(ty.clone())), &mut typing),
+                        ty => render(ty, &mut typing)
+                    };
+                    // The JSON key of a renamed field is kept in its metadata, for loading and dumping the original JSON
+                    let default = match (property.optional, field != property.key) {
+                        (optional, true) => {
+                            renamed = true;
+                            let default = if optional {"default=None, "} else {""};
+                            format!(" = field({default}metadata={{\"json\": {}}})", quote(&property.key))
+                        },
+                        (true, false) => String::from(" = None"),
+                        (false, false) => String::new()
+                    };
+                    lines.push(format!("    {field}: {ty}{default}"));
+                }
+                if properties.is_empty() {
+                    lines.push(String::from("    pass"));
+                }
+                lines.join("\n")
+            },
+            Definition::Enum {name, values} => {
+                if !typing.contains(&"Literal") {
+                    typing.push("Literal");
+                }
+                format!("{name} = Literal[{}]", values.iter().map(|value| quote(value)).collect::<Vec<String>>().join(", "))
+            }
+        })
+        .collect::<Vec<String>>();
+
+    // Annotations are evaluated lazily, so a class can use types defined after it
+    let mut imports = vec![String::from("from __future__ import annotations"), String::new()];
+    if renamed {
+        imports.insert(0, String::from("# Fields named differently than their JSON key have the key in `field(metadata={\"json\": ...})`"));
+        imports.push(String::from("from dataclasses import dataclass, field"));
+    } else if dataclasses {
+        imports.push(String::from("from dataclasses import dataclass"));
+    }
+    if !typing.is_empty() {
+        typing.sort();
+        imports.push(format!("from typing import {}", typing.join(", ")));
+    }
+    format!("{}\n\n\n{}", imports.join("\n"), blocks.join("\n\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use crate::json::infer;
+    use super::*;
+    use pretty_assertions::{assert_eq};
+
+    fn generate_for(language: Language) -> Result<String> {
+        let samples = [
+            json!({"type": 1, "self": "a", "user_name": "b", "userName": "c", "class": true, "user": {"id": 1}, "1st": null}),
+            json!({"type": 2, "self": "d", "user_name": "e", "userName": "f", "user": {"id": 2}})
+        ];
+        generate(&infer::infer_all(&samples), "User", language)
+    }
+
+    #[test]
+    fn rename_keywords_and_collisions() -> Result<()> {
+        assert_eq!(generate_for(Language::Rust)?, [
+            "use serde::{Deserialize, Serialize};",
+            "#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct User {\n    pub r#type: i64,\n    #[serde(rename = \"self\")]\n    pub self_: String,\n    pub user_name: String,\n    #[serde(rename = \"userName\")]\n    pub user_name2: String,\n    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub class: Option<bool>,\n    pub user: User2,\n    #[serde(rename = \"1st\")]\n    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub field_1_st: Option<serde_json::Value>,\n}",
+            "#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct User2 {\n    pub id: i64,\n}"
+        ].join("\n\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn generate_go_and_python() -> Result<()> {
+        assert_eq!(generate_for(Language::Go)?, [
+            "type User struct {\n\tType      int64  `json:\"type\"`\n\tSelf      string `json:\"self\"`\n\tUserName  string `json:\"user_name\"`\n\tUserName2 string `json:\"userName\"`\n\tClass     *bool  `json:\"class,omitempty\"`\n\tUser      User2  `json:\"user\"`\n\tField1St  any    `json:\"1st,omitempty\"`\n}",
+            "type User2 struct {\n\tId int64 `json:\"id\"`\n}"
+        ].join("\n\n"));
+        assert_eq!(generate_for(Language::Python)?, [
+            "# Fields named differently than their JSON key have the key in `field(metadata={\"json\": ...})`\nfrom __future__ import annotations\n\nfrom dataclasses import dataclass, field\nfrom typing import Any, Optional",
+            "@dataclass\nclass User:\n    type: int\n    self: str\n    user_name: str\n    user_name2: str = field(metadata={\"json\": \"userName\"})\n    user: User2\n    class_: Optional[bool] = field(default=None, metadata={\"json\": \"class\"})\n    field_1_st: Any = field(default=None, metadata={\"json\": \"1st\"})",
+            "@dataclass\nclass User2:\n    id: int"
+        ].join("\n\n\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn name_types_after_singular_keys() {
+        assert_eq!(singular("tags"), "tag");
+        assert_eq!(singular("categories"), "category");
+        assert_eq!(singular("addresses"), "address");
+        assert_eq!(singular("status"), "status");
+        assert_eq!(singular("class"), "class");
+        assert_eq!(singular("data"), "dataItem");
+    }
+
+    #[test]
+    fn rename_reserved_type_names() -> Result<()> {
+        let shape = infer::infer_all(&[json!({"self": {"id": 1}, "string": {"id": 2}, "status": ["self", "done", "done"]})]);
+        assert_eq!(generate(&shape, "root", Language::Rust)?, [
+            "use serde::{Deserialize, Serialize};",
+            "#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct Root {\n    #[serde(rename = \"self\")]\n    pub self_: SelfType,\n    pub string: StringType,\n    pub status: Vec<Status>,\n}",
+            "#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct SelfType {\n    pub id: i64,\n}",
+            "#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct StringType {\n    pub id: i64,\n}",
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]\npub enum Status {\n    #[serde(rename = \"self\")]\n    SelfValue,\n    #[serde(rename = \"done\")]\n    Done,\n}"
+        ].join("\n\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn fit_integers_above_i64() -> Result<()> {
+        let shape = infer::infer_all(&[json!({"id": 1, "big": 1, "mixed": -1}), json!({"id": 2, "big": 18446744073709551615u64, "mixed": 18446744073709551615u64})]);
+        assert_eq!(generate(&shape, "Row", Language::Rust)?, [
+            "use serde::{Deserialize, Serialize};",
+            "#[derive(Debug, Clone, Serialize, Deserialize)]\npub struct Row {\n    pub id: i64,\n    pub big: u64,\n    pub mixed: f64,\n}"
+        ].join("\n\n"));
+        Ok(())
+    }
+}
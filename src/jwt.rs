@@ -0,0 +1,35 @@
+//! Decoding and verification of JSON Web Tokens
+
+use std::collections::HashSet;
+use jsonwebtoken::{DecodingKey, Header, Validation};
+use serde::Serialize;
+use serde_json::Value;
+use crate::{Error, Result};
+
+pub use jsonwebtoken::Algorithm;
+
+/// Header and claims of a JWT
+#[derive(Debug, Serialize)]
+pub struct DecodedToken {
+    pub header: Header,
+    pub claims: Value
+}
+
+/// Decodes the token **without** verifying its signature
+pub fn decode(token: &str) -> Result<DecodedToken> {
+    jsonwebtoken::dangerous::insecure_decode::<Value>(token.trim())
+        .map(|data| DecodedToken {header: data.header, claims: data.claims})
+        .map_err(|err| Error::InvalidInput(format!("Failed to decode JWT! {err}")))
+}
+
+/// Verifies the signature of the token with a shared secret and checks that all `required_claims` are present.
+/// Returns the decoded token when it is valid.
+pub fn verify(token: &str, secret: &[u8], algorithm: Algorithm, required_claims: &[String]) -> Result<DecodedToken> {
+    let key = DecodingKey::from_secret(secret);
+    let mut validation = Validation::new(algorithm);
+    validation.required_spec_claims = HashSet::from_iter(required_claims.iter().cloned());
+
+    jsonwebtoken::decode::<Value>(token.trim(), &key, &validation)
+        .map(|data| DecodedToken {header: data.header, claims: data.claims})
+        .map_err(|err| Error::InvalidInput(format!("The JWT Token is invalid! {err}")))
+}
@@ -0,0 +1,25 @@
+//! Developer utilities behind the `dx` command line tool.
+//!
+//! Every domain lives in its own module and exposes plain functions taking typed inputs
+//! and returning typed results, so the same logic can be reused outside of the CLI.
+//!
+//! ```
+//! let hash = dx::hash::digest(dx::hash::HashAlgorithm::Sha256, b"Hello, World");
+//! assert_eq!(hash, "03675ac53ff9cd1535ccc7dfcdfa2c458c5218371f418dc136f2d19ac1fbe8a5");
+//! ```
+
+mod error;
+
+pub mod convert;
+pub mod csv;
+pub mod encode;
+pub mod hash;
+pub mod json;
+pub mod jwt;
+pub mod lorem;
+pub mod regex;
+pub mod text;
+pub mod time;
+pub mod uuid;
+
+pub use crate::error::{Error, Result};
@@ -0,0 +1,32 @@
+//! Lorem Ipsum generation
+
+use rand::Rng;
+
+/// Generates `count` random words
+pub fn words(count: u32) -> String {
+    let mut rng = rand::rng();
+    lipsum::lipsum_words_from_seed(count as usize, rng.random())
+}
+
+/// Generates `count` random sentences of 5 to 11 words
+pub fn sentences(count: u32) -> Vec<String> {
+    let mut rng = rand::rng();
+    (1..=count).map(|_| {
+        let num_words = rng.random_range(5..12);
+        let seed = rng.random();
+        lipsum::lipsum_words_from_seed(num_words, seed)
+    }).collect()
+}
+
+/// Generates `count` random paragraphs of 5 to 6 sentences
+pub fn paragraphs(count: u32) -> Vec<String> {
+    let mut rng = rand::rng();
+    (1..=count).map(|_| {
+        let num_sentences = rng.random_range(5..7);
+        (1..=num_sentences).map(|_| {
+            let num_words = rng.random_range(10..25);
+            let seed = rng.random();
+            lipsum::lipsum_words_from_seed(num_words, seed)
+        }).collect::<Vec<String>>().join(" ")
+    }).collect()
+}
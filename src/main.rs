@@ -1,31 +1,65 @@
-use clap::Parser;
-use colored::Colorize;
-use crate::cli::Cli;
+use std::process::exit;
+use crate::cli::{Command, OutputFormat};
 use crate::handlers::{CommandHandler, CommandHandlerError};
 
+mod batch;
 mod cli;
+mod config;
+mod input;
 mod handlers;
 mod output;
+mod shell;
+mod stream;
 mod utils;
 
 fn main() {
-    let cli = Cli::parse();
-    let handler = CommandHandler::new(cli.command);
-    match handler.handle() {
-        Ok(result) => {
-            if cli.output.is_some() {
-                output::save_to_file(&result, cli.output.as_ref().unwrap());
-                println!("{}", format!("Results saved successfully to {}", cli.output.unwrap()).green().bold());
-            } else {
-                output::print_success(&result);
+    // An invalid config file must not stop `dx config set` from repairing it
+    if let Err(err) = config::init() {
+        output::print_warning(&format!("{err}\nThe configuration is ignored until it's fixed."));
+    }
+
+    let cli = config::parse_cli();
+    let mode = output::init(cli.raw || cli.output_format == OutputFormat::Json, cli.color);
+    if cli.each_line || cli.batch.is_some() {
+        match batch::run(cli.command, cli.batch.as_deref(), &cli.output_format, cli.output.as_deref()) {
+            Ok(exit_code) => exit(exit_code),
+            Err(err) => {
+                output::print_error(&err, &cli.output_format, mode);
+                exit(err.exit_code());
             }
-        },
-        Err(err) => {
-            if let CommandHandlerError::NegativeResult(message) = err {
-                output::print_negative_result(&message)
-            } else {
-                output::print_error(&err.to_string())
+        }
+    }
+
+    if stream::requested(&cli.command) {
+        match stream::run(cli.command, &cli.output_format, cli.output.as_deref()) {
+            Ok(exit_code) => exit(exit_code),
+            Err(err) => {
+                output::print_error(&err, &cli.output_format, mode);
+                exit(err.exit_code());
             }
         }
+    }
+
+    let newline = output::trailing_newline(&cli.command);
+    let result = match cli.command {
+        Command::Shell => shell::run(&cli.output_format),
+        command => CommandHandler::new(command).handle().and_then(|result| {
+            let result = result.render(&cli.output_format);
+            if let Some(path) = &cli.output {
+                output::save_to_file(&result, path, mode)
+            } else {
+                output::print_success(&result, mode, newline);
+                Ok(())
+            }
+        })
     };
+
+    if let Err(err) = result {
+        match &err {
+            CommandHandlerError::NegativeResult(message) => output::print_negative_result(message, &cli.output_format, mode),
+            CommandHandlerError::NegativeOutput(result) => output::print_negative_output(result, &cli.output_format, mode),
+            err => output::print_error(err, &cli.output_format, mode)
+        }
+        exit(err.exit_code());
+    }
 }
@@ -1,31 +1,104 @@
-use std::fs;
-use std::process::exit;
+use std::{env, fs, io, result};
+use std::io::IsTerminal;
 use colored::Colorize;
+use crate::cli::{ColorMode, Command, JSONMethod, OutputFormat};
+use crate::handlers::{CommandHandlerError, CommandOutput};
 
-pub fn save_to_file(content: &str, path: &str) {
-    if let Err(err) = fs::write(path, content) {
-        eprintln!("{}", format!("Failed to save results to file! {err}").red().bold());
-        exit(1);
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputMode {
+    /// Results wrapped in coloured banners, meant for humans
+    Pretty,
+    /// Only the result on stdout and diagnostics on stderr, meant for pipes and `$(...)`
+    Raw
+}
+
+/// Picks the output mode and configures colours. Raw mode is used when requested or when stdout is not a TTY.
+pub fn init(raw: bool, color: ColorMode) -> OutputMode {
+    let mode = if raw || !io::stdout().is_terminal() {OutputMode::Raw} else {OutputMode::Pretty};
+
+    let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    match color {
+        ColorMode::Always => colored::control::set_override(true),
+        ColorMode::Never => colored::control::set_override(false),
+        ColorMode::Auto if no_color || (mode == OutputMode::Raw && !io::stderr().is_terminal()) => colored::control::set_override(false),
+        ColorMode::Auto => {}
     }
+
+    mode
 }
 
-pub fn print_success(content: &str) {
-    println!("{}", format!("\nProgram finished successfully!\n").green());
-    println!("{}", format!("------ RESULT START ------\n").green().bold());
+pub fn save_to_file(content: &str, path: &str, mode: OutputMode) -> result::Result<(), CommandHandlerError> {
+    fs::write(path, content)
+        .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to save results to file! {err}"))))?;
+
+    let message = format!("Results saved successfully to {}", path).green().bold();
+    match mode {
+        OutputMode::Pretty => println!("{message}"),
+        OutputMode::Raw => eprintln!("{message}")
+    }
+    Ok(())
+}
+
+/// Whether the printed result ends with a line break, which `json format --no-trailing-newline` turns off
+pub fn trailing_newline(command: &Command) -> bool {
+    !matches!(command, Command::Json {method: JSONMethod::Format {options}, ..} if options.no_trailing_newline)
+}
+
+pub fn print_success(content: &str, mode: OutputMode, newline: bool) {
+    if mode == OutputMode::Raw {
+        if newline {println!("{content}")} else {print!("{content}")}
+        return;
+    }
+
+    println!("{}", "\nProgram finished successfully!\n".green());
+    println!("{}", "------ RESULT START ------\n".green().bold());
     println!("{content}");
-    println!("{}", format!("\n------ RESULT END ------").green().bold());
+    println!("{}", "\n------ RESULT END ------".green().bold());
+}
+
+pub fn print_error(error: &CommandHandlerError, format: &OutputFormat, mode: OutputMode) {
+    if *format == OutputFormat::Json {
+        eprintln!("{}", serde_json::json!({"error": {"code": error.exit_code(), "message": error.to_string()}}));
+        return;
+    }
+
+    if mode == OutputMode::Raw {
+        eprintln!("{} {error}", "error:".red().bold());
+        return;
+    }
+
+    eprintln!("{}", "\nProgram didn't finish successfully!\n".red());
+    eprintln!("{}", "------ ERROR START ------\n".red().bold());
+    eprintln!("{error}");
+    eprintln!("{}", "\n------ ERROR END ------".red().bold());
 }
 
-pub fn print_error(error: &str) {
-    println!("{}", format!("\nProgram didn't finish successfully!\n").red());
-    println!("{}", format!("------ ERROR START ------\n").red().bold());
-    println!("{error}");
-    println!("{}", format!("\n------ ERROR END ------").red().bold());
+/// Prints the data of the negative result for `--output-format json` and its text otherwise
+pub fn print_negative_output(output: &CommandOutput, format: &OutputFormat, mode: OutputMode) {
+    if *format == OutputFormat::Json {
+        println!("{}", output.render(format));
+        return;
+    }
+    print_negative_result(&output.text, format, mode);
 }
 
-pub fn print_negative_result(content: &str) {
-    println!("{}", format!("\nProgram finished successfully!\n").green());
-    println!("{}", format!("------ RESULT START ------\n").green().bold());
-    println!("{}", format!("{content}").red());
-    println!("{}", format!("\n------ RESULT END ------").green().bold());
-}
\ No newline at end of file
+pub fn print_warning(message: &str) {
+    eprintln!("{} {message}", "warning:".yellow().bold());
+}
+
+pub fn print_negative_result(content: &str, format: &OutputFormat, mode: OutputMode) {
+    if *format == OutputFormat::Json {
+        println!("{}", serde_json::json!({"success": false, "message": content}));
+        return;
+    }
+
+    if mode == OutputMode::Raw {
+        eprintln!("{}", content.red());
+        return;
+    }
+
+    println!("{}", "\nProgram finished successfully!\n".green());
+    println!("{}", "------ RESULT START ------\n".green().bold());
+    println!("{}", content.red());
+    println!("{}", "\n------ RESULT END ------".green().bold());
+}
@@ -0,0 +1,77 @@
+//! Regular expression matching and replacing
+
+use ::regex::{Captures, Regex};
+use serde::Serialize;
+use crate::{Error, Result};
+
+/// A single match of a pattern
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RegexMatch {
+    /// Position of the match among all matches
+    pub index: usize,
+    /// Byte offset where the match starts
+    pub start: usize,
+    /// Byte offset where the match ends
+    pub end: usize,
+    pub text: String,
+    /// Capture groups of the match. Groups which didn't participate in the match are `None`
+    pub groups: Vec<Option<RegexGroup>>
+}
+
+/// A capture group of a match
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RegexGroup {
+    pub index: usize,
+    pub name: Option<String>,
+    pub start: usize,
+    pub end: usize,
+    pub text: String
+}
+
+/// Compiles the pattern
+pub fn compile(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern)
+        .map_err(|_| Error::InvalidInput(String::from("Invalid RegEx expression!")))
+}
+
+/// Returns the first match of the pattern in the text
+pub fn first_match(pattern: &str, text: &str) -> Result<Option<RegexMatch>> {
+    let re = compile(pattern)?;
+    Ok(re.captures(text).map(|captures| to_match(&re, 0, &captures)))
+}
+
+/// Returns all matches of the pattern in the text
+pub fn matches(pattern: &str, text: &str) -> Result<Vec<RegexMatch>> {
+    let re = compile(pattern)?;
+    Ok(
+        re.captures_iter(text).enumerate()
+            .map(|(capture_i, captures)| to_match(&re, capture_i, &captures))
+            .collect()
+    )
+}
+
+/// Replaces all matches of the pattern. The replacement can reference groups with `$1` or `$name`.
+pub fn replace(pattern: &str, text: &str, replacement: &str) -> Result<String> {
+    let re = compile(pattern)?;
+    Ok(re.replace_all(text, replacement).to_string())
+}
+
+fn to_match(re: &Regex, index: usize, captures: &Captures) -> RegexMatch {
+    // Group 0 is always present, it's the whole match
+    let full_match = captures.get(0).unwrap();
+    RegexMatch {
+        index,
+        start: full_match.start(),
+        end: full_match.end(),
+        text: full_match.as_str().to_string(),
+        groups: re.capture_names().enumerate().skip(1).map(|(group_i, name)| {
+            captures.get(group_i).map(|group| RegexGroup {
+                index: group_i,
+                name: name.map(String::from),
+                start: group.start(),
+                end: group.end(),
+                text: group.as_str().to_string()
+            })
+        }).collect()
+    }
+}
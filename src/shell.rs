@@ -0,0 +1,221 @@
+use std::fs;
+use std::path::PathBuf;
+use std::result;
+use clap::CommandFactory;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use crate::cli::{Command, CommandLine, OutputFormat};
+use crate::config;
+use crate::handlers::{CommandHandler, CommandHandlerError, CommandOutput};
+use crate::input::InputSource;
+use crate::output::{self, OutputMode};
+
+/// Placeholder replaced with the result of the previous command
+const PREVIOUS_RESULT: &str = "$_";
+
+const EXIT_COMMANDS: [&str; 2] = ["exit", "quit"];
+
+/// Runs the interactive shell until `exit`, `quit` or Ctrl-D
+pub fn run(format: &OutputFormat) -> result::Result<(), CommandHandlerError> {
+    let mut editor = Editor::<ShellHelper, DefaultHistory>::new()
+        .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to start the interactive shell! {err}"))))?;
+    editor.set_helper(Some(ShellHelper::new()));
+
+    let history_path = history_path();
+    if let Some(path) = &history_path {
+        // The history file doesn't exist on the first run
+        let _ = editor.load_history(path);
+    }
+
+    println!("dx interactive shell. Use '{PREVIOUS_RESULT}' to reference the previous result, 'exit' to quit.");
+    let mut previous: Option<String> = None;
+    loop {
+        let line = match editor.readline("dx> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(CommandHandlerError::RuntimeError(Some(format!("Failed to read the command! {err}"))))
+        };
+
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+        if EXIT_COMMANDS.contains(&line) {
+            break;
+        }
+
+        let command = match parse_line(line, previous.as_deref()) {
+            Ok(Some(command)) => command,
+            Ok(None) => continue,
+            Err(err) => {
+                output::print_error(&err, format, OutputMode::Raw);
+                continue;
+            }
+        };
+
+        match execute(command, previous.as_deref()) {
+            Ok(result) => {
+                output::print_success(&result.render(format), OutputMode::Raw, true);
+                previous = Some(result.text);
+            },
+            Err(CommandHandlerError::NegativeResult(message)) => output::print_negative_result(&message, format, OutputMode::Raw),
+            Err(CommandHandlerError::NegativeOutput(result)) => output::print_negative_output(&result, format, OutputMode::Raw),
+            Err(err) => output::print_error(&err, format, OutputMode::Raw)
+        }
+    }
+
+    if let Some(path) = &history_path {
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        editor.save_history(path)
+            .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to save the shell history! {err}"))))?;
+    }
+
+    Ok(())
+}
+
+fn history_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("dx").join("shell_history"))
+}
+
+/// Splits the line shell-style and parses it. Returns `None` when clap already printed help or a usage error.
+fn parse_line(line: &str, previous: Option<&str>) -> result::Result<Option<Command>, CommandHandlerError> {
+    let mut tokens = shlex::split(line)
+        .ok_or_else(|| CommandHandlerError::InvalidArguments(String::from("Failed to parse the command! Check for unclosed quotes")))?;
+
+    if tokens.iter().any(|token| token.contains(PREVIOUS_RESULT)) {
+        let previous = previous
+            .ok_or_else(|| CommandHandlerError::InvalidArguments(format!("There is no previous result to reference with '{PREVIOUS_RESULT}'!")))?;
+        for token in tokens.iter_mut() {
+            *token = token.replace(PREVIOUS_RESULT, previous);
+        }
+    }
+
+    match config::parse_command_line(tokens) {
+        Ok(command_line) => Ok(Some(command_line.command)),
+        Err(err) => {
+            let _ = err.print();
+            Ok(None)
+        }
+    }
+}
+
+/// Runs the command. Omitted inputs are read from the previous result.
+fn execute(command: Command, previous: Option<&str>) -> result::Result<CommandOutput, CommandHandlerError> {
+    let handler = CommandHandler::new(command);
+    let mut handler = match previous {
+        Some(previous) => handler.with_stdin(InputSource::from_bytes(previous.as_bytes())),
+        None => handler
+    };
+    handler.handle()
+}
+
+/// Tab completion of subcommands, options and their possible values based on the clap command tree
+struct ShellHelper {
+    command: clap::Command
+}
+
+impl ShellHelper {
+    fn new() -> Self {
+        let mut command = config::get().apply_defaults(CommandLine::command());
+        command.build();
+        ShellHelper {command}
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let prefix = &line[start..];
+
+        let mut command = &self.command;
+        let mut previous_arg = None;
+        for word in line[..start].split_whitespace() {
+            if let Some(subcommand) = command.find_subcommand(word) {
+                command = subcommand;
+                previous_arg = None;
+            } else {
+                previous_arg = command.get_arguments().find(|arg| {
+                    word.strip_prefix("--").is_some_and(|long| arg.get_long() == Some(long)) ||
+                        word.strip_prefix('-').is_some_and(|short| short.len() == 1 && arg.get_short().is_some_and(|s| short.starts_with(s)))
+                });
+            }
+        }
+
+        let candidates = if let Some(arg) = previous_arg.filter(|arg| arg.get_action().takes_values()) {
+            arg.get_possible_values().iter().map(|value| value.get_name().to_string()).collect::<Vec<String>>()
+        } else if prefix.starts_with('-') {
+            command.get_arguments().filter_map(|arg| arg.get_long()).map(|long| format!("--{long}")).collect()
+        } else {
+            let mut names = command.get_subcommands().map(|subcommand| subcommand.get_name().to_string()).collect::<Vec<String>>();
+            if std::ptr::eq(command, &self.command) {
+                names.extend(EXIT_COMMANDS.iter().map(|name| name.to_string()));
+            }
+            names
+        };
+
+        Ok((
+            start,
+            candidates.into_iter()
+                .filter(|candidate| candidate.starts_with(prefix))
+                .map(|candidate| Pair {display: candidate.clone(), replacement: candidate})
+                .collect()
+        ))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rustyline::history::DefaultHistory;
+    use super::*;
+
+    #[test]
+    fn reference_previous_result() {
+        let command = parse_line("text upper $_", Some("hello")).unwrap().unwrap();
+        let result = execute(command, None);
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().text, "HELLO");
+    }
+
+    #[test]
+    fn read_omitted_input_from_previous_result() {
+        let command = parse_line("encode hex", Some("dx")).unwrap().unwrap();
+        let result = execute(command, Some("dx"));
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().text, "6478");
+    }
+
+    #[test]
+    fn complete_subcommands() {
+        let helper = ShellHelper::new();
+        let history = DefaultHistory::new();
+        let (start, candidates) = helper.complete("hash sh", 7, &Context::new(&history)).unwrap();
+
+        assert_eq!(start, 5);
+        assert_eq!(candidates.iter().map(|pair| pair.replacement.as_str()).collect::<Vec<&str>>(), vec!["sha256", "sha512"]);
+    }
+}
@@ -0,0 +1,155 @@
+use std::cell::Cell;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufWriter, IsTerminal, Read, Write};
+use std::rc::Rc;
+use std::result;
+use std::time::{Duration, Instant};
+use colored::Colorize;
+use crate::cli::{Command, JSONMethod, OutputFormat};
+use crate::handlers::CommandHandlerError;
+use crate::handlers::JSONHandler;
+use crate::input::InputSource;
+
+/// How often the progress line is redrawn
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    pub records: usize,
+    /// Records that didn't match the schema
+    pub invalid: usize
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Processed {} records", self.records)?;
+        if self.invalid > 0 {
+            write!(f, ", {} invalid", self.invalid)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether the command reads its input as a stream (`json format|minify|validate|query --stream`)
+pub fn requested(command: &Command) -> bool {
+    match command {
+        Command::Json {method, ..} => match method {
+            JSONMethod::Format {options} => options.stream,
+            JSONMethod::Minify {options} => options.stream,
+            JSONMethod::Validate {options} => options.stream,
+            JSONMethod::Query {options} => options.stream,
+            _ => false
+        },
+        _ => false
+    }
+}
+
+/// Runs the streaming command, writing results to the output file or stdout as they are produced.
+/// Returns the exit code, 1 when some records didn't match the schema.
+pub fn run(command: Command, format: &OutputFormat, output: Option<&str>) -> result::Result<i32, CommandHandlerError> {
+    let Command::Json {lenient, method} = command else {
+        return Err(CommandHandlerError::InvalidArguments(String::from("Only json commands can be streamed!")));
+    };
+    if lenient {
+        return Err(CommandHandlerError::InvalidArguments(String::from("JSON5 input can't be streamed, remove --lenient!")));
+    }
+    if *format == OutputFormat::Json {
+        return Err(CommandHandlerError::InvalidArguments(String::from("Streamed results are written as they are produced and can't be wrapped in --output-format json!")));
+    }
+
+    let writer: Box<dyn Write> = match output {
+        Some(path) => Box::new(File::create(path)
+            .map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to create the output file '{path}'! {err}"))))?),
+        None => Box::new(io::stdout().lock())
+    };
+    let mut writer = BufWriter::new(writer);
+    // The progress line would get mixed up with results printed to the same terminal
+    let show_progress = io::stderr().is_terminal() && (output.is_some() || !io::stdout().is_terminal());
+
+    let summary = JSONHandler::stream(&method, &mut InputSource::stdin(), &mut writer, show_progress)?;
+    writer.flush().map_err(|err| CommandHandlerError::RuntimeError(Some(format!("Failed to write the result! {err}"))))?;
+
+    let message = summary.to_string();
+    eprintln!("{}", if summary.invalid == 0 {message.green()} else {message.yellow()});
+    Ok(if summary.invalid == 0 {0} else {1})
+}
+
+/// Progress of a long run on stderr: records processed and bytes read, out of the file size when known
+pub struct Progress {
+    read: Rc<Cell<u64>>,
+    total: Option<u64>,
+    records: usize,
+    enabled: bool,
+    drawn: bool,
+    last_draw: Instant
+}
+
+/// Input counting the bytes read for [`Progress`]
+pub struct Tracked<R> {
+    inner: R,
+    read: Rc<Cell<u64>>
+}
+
+impl<R: Read> Read for Tracked<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let length = self.inner.read(buf)?;
+        self.read.set(self.read.get() + length as u64);
+        Ok(length)
+    }
+}
+
+impl Progress {
+    pub fn new(total: Option<u64>, enabled: bool) -> Self {
+        // Runs shorter than the interval never draw anything
+        Progress {read: Rc::new(Cell::new(0)), total, records: 0, enabled, drawn: false, last_draw: Instant::now()}
+    }
+
+    pub fn track<R: Read>(&self, inner: R) -> Tracked<R> {
+        Tracked {inner, read: Rc::clone(&self.read)}
+    }
+
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    pub fn record(&mut self) {
+        self.records += 1;
+        if self.enabled && self.last_draw.elapsed() >= PROGRESS_INTERVAL {
+            self.draw();
+        }
+    }
+
+    /// Clears the progress line
+    pub fn finish(&mut self) {
+        if self.drawn {
+            eprint!("\r\x1b[2K");
+            self.drawn = false;
+        }
+    }
+
+    fn draw(&mut self) {
+        let read = self.read.get();
+        let position = match self.total.filter(|total| *total > 0) {
+            Some(total) => format!("{} of {} ({}%)", size(read), size(total), read.min(total) * 100 / total),
+            None => size(read)
+        };
+        eprint!("\r\x1b[2KProcessed {} records, {position}", self.records);
+        self.drawn = true;
+        self.last_draw = Instant::now();
+    }
+}
+
+fn size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
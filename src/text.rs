@@ -0,0 +1,28 @@
+//! Text case conversions
+
+use capitalize::Capitalize;
+use stringcase::{camel_case, kebab_case, pascal_case, snake_case};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Case {
+    Upper,
+    Lower,
+    Title,
+    Camel,
+    Pascal,
+    Snake,
+    Kebab
+}
+
+/// Converts the text to the given case
+pub fn convert(text: &str, case: Case) -> String {
+    match case {
+        Case::Upper => text.to_uppercase(),
+        Case::Lower => text.to_lowercase(),
+        Case::Title => text.capitalize(),
+        Case::Camel => camel_case(text),
+        Case::Pascal => pascal_case(text),
+        Case::Snake => snake_case(text),
+        Case::Kebab => kebab_case(text)
+    }
+}
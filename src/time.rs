@@ -0,0 +1,143 @@
+//! Conversion and formatting of dates and Unix timestamps
+
+use std::fmt::{Display, Formatter};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::Serialize;
+use crate::{Error, Result};
+
+pub use chrono_tz::{Tz, TZ_VARIANTS};
+
+/// A point in time in multiple representations
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Timestamp {
+    pub unix: i64,
+    pub unix_millis: i64,
+    pub rfc3339: String,
+    pub rfc2822: String,
+    pub utc: String,
+    pub timezone: String
+}
+
+impl Timestamp {
+    pub fn new<T: TimeZone>(date: &DateTime<T>, timezone: &str) -> Self where T::Offset: Display {
+        Timestamp {
+            unix: date.timestamp(),
+            unix_millis: date.timestamp_millis(),
+            rfc3339: date.to_rfc3339(),
+            rfc2822: date.to_rfc2822(),
+            utc: date.to_utc().to_rfc3339(),
+            timezone: timezone.to_string()
+        }
+    }
+}
+
+/// Difference between two points in time split into units
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RelativeTime {
+    /// Signed difference in seconds. Positive when the date is before the start
+    pub total_seconds: i64,
+    pub weeks: i64,
+    pub days: i64,
+    pub hours: i64,
+    pub minutes: i64,
+    pub seconds: i64
+}
+
+impl Display for RelativeTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.weeks > 0 {
+            write!(f, "{} Weeks ", self.weeks)?;
+        }
+        if self.days > 0 {
+            write!(f, "{} Days ", self.days)?;
+        }
+        if self.hours > 0 {
+            write!(f, "{} Hours ", self.hours)?;
+        }
+        if self.minutes > 0 {
+            write!(f, "{} Minutes ", self.minutes)?;
+        }
+        write!(f, "{} Seconds", self.seconds)
+    }
+}
+
+/// Parses an IANA timezone name like `Europe/Sofia`
+pub fn parse_timezone(timezone: &str) -> Result<Tz> {
+    timezone.parse()
+        .map_err(|_| Error::InvalidInput(format!("Timezone '{}' is invalid! Run 'dx time tz' to list all available timezones", timezone)))
+}
+
+/// Converts a Unix timestamp in seconds to a UTC date
+pub fn from_unix(timestamp: i64) -> Result<DateTime<Utc>> {
+    DateTime::from_timestamp(timestamp, 0)
+        .ok_or_else(|| Error::InvalidInput(format!("Timestamp '{}' is invalid!", timestamp)))
+}
+
+/// Parses a date using a strftime/strptime format. Dates without timezone in the format are treated as UTC
+/// and dates without time as midnight.
+pub fn parse(date: &str, format: &str) -> Result<DateTime<FixedOffset>> {
+    let err_convert = |_| Error::InvalidInput(format!("Failed to parse date '{}'!", date));
+
+    // Check if timezone is included
+    if has_format_timezone(format) {
+        DateTime::parse_from_str(date, format).map_err(err_convert)
+    } else if has_format_time(format) {
+        let date = NaiveDateTime::parse_from_str(date, format).map_err(err_convert)?;
+        Ok(date.and_utc().fixed_offset())
+    } else {
+        let date = NaiveDate::parse_from_str(date, format).map_err(err_convert)?;
+        Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc().fixed_offset())
+    }
+}
+
+/// Parses the date with the `from` format and formats it with the `target` format
+pub fn reformat(date: &str, from: &str, target: &str) -> Result<String> {
+    let err_convert = |_| Error::InvalidInput(format!("Failed to parse date '{}'!", date));
+
+    if has_format_timezone(from) {
+        let date = DateTime::parse_from_str(date, from).map_err(err_convert)?;
+        Ok(date.format(target).to_string())
+    } else if has_format_time(from) {
+        let date = NaiveDateTime::parse_from_str(date, from).map_err(err_convert)?;
+        Ok(date.format(target).to_string())
+    } else {
+        let date = NaiveDate::parse_from_str(date, from).map_err(err_convert)?;
+        Ok(date.format(target).to_string())
+    }
+}
+
+/// Calculates how much time passed between the date and the start
+pub fn relative(date: DateTime<Utc>, start: DateTime<Utc>) -> RelativeTime {
+    let duration = start - date;
+    let mut seconds = duration.num_seconds().abs();
+
+    let weeks = seconds / Duration::weeks(1).num_seconds();
+    seconds %= Duration::weeks(1).num_seconds();
+
+    let days = seconds / Duration::days(1).num_seconds();
+    seconds %= Duration::days(1).num_seconds();
+
+    let hours = seconds / Duration::hours(1).num_seconds();
+    seconds %= Duration::hours(1).num_seconds();
+
+    let minutes = seconds / Duration::minutes(1).num_seconds();
+    seconds %= Duration::minutes(1).num_seconds();
+
+    RelativeTime {
+        total_seconds: duration.num_seconds(),
+        weeks,
+        days,
+        hours,
+        minutes,
+        seconds
+    }
+}
+
+fn has_format_timezone(format: &str) -> bool {
+    ["%Z", "%z"].iter().any(|pattern| format.contains(pattern))
+}
+
+fn has_format_time(format: &str) -> bool {
+    format.contains("%H") || format.contains("%M") ||
+        format.contains("%S") || format.contains("%T")
+}
@@ -1,4 +1,33 @@
-use std::result;
-
 pub type TestError = String;
-pub type TestResult = result::Result<(), TestError>;
+
+#[cfg(test)]
+pub type TestResult = std::result::Result<(), TestError>;
+
+/// File in the temp directory for tests reading from disk, removed when dropped.
+/// The name includes the process id and a counter so tests running at the same time don't share files.
+#[cfg(test)]
+pub struct TempFile {
+    pub path: std::path::PathBuf
+}
+
+#[cfg(test)]
+impl TempFile {
+    pub fn new(content: &str) -> std::result::Result<TempFile, TestError> {
+        static COUNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let count = COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("dx_test_{}_{count}.json", std::process::id()));
+        std::fs::write(&path, content).map_err(|err| err.to_string())?;
+        Ok(TempFile {path})
+    }
+
+    pub fn path(&self) -> String {
+        self.path.display().to_string()
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,13 @@
+//! UUID generation
+
+pub use ::uuid::Uuid;
+
+/// Generates `count` random (version 4) UUIDs
+pub fn v4(count: u32) -> Vec<Uuid> {
+    (0..count).map(|_| Uuid::new_v4()).collect()
+}
+
+/// Generates `count` time-ordered (version 7) UUIDs
+pub fn v7(count: u32) -> Vec<Uuid> {
+    (0..count).map(|_| Uuid::now_v7()).collect()
+}